@@ -0,0 +1,5 @@
+ALTER TABLE utilisateur
+    DROP CONSTRAINT utilisateur_role_check,
+    ALTER COLUMN role DROP DEFAULT;
+
+UPDATE utilisateur SET role = 'User' WHERE role = 'client';
@@ -0,0 +1,6 @@
+-- Rôles typés : client, personnel, admin
+UPDATE utilisateur SET role = 'client' WHERE role NOT IN ('client', 'personnel', 'admin');
+
+ALTER TABLE utilisateur
+    ALTER COLUMN role SET DEFAULT 'client',
+    ADD CONSTRAINT utilisateur_role_check CHECK (role IN ('client', 'personnel', 'admin'));
@@ -0,0 +1 @@
+DROP TABLE jetons_usage_unique;
@@ -0,0 +1,13 @@
+-- Table: Jetons à usage unique (réinitialisation de mot de passe, ...)
+-- Seul le SHA-256 du jeton est stocké
+CREATE TABLE jetons_usage_unique (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    utilisateur_id UUID NOT NULL REFERENCES utilisateur(id),
+    objet VARCHAR(30) NOT NULL,
+    jeton_hache VARCHAR(64) NOT NULL UNIQUE,
+    date_expiration TIMESTAMPTZ NOT NULL,
+    date_utilisation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX jetons_usage_unique_utilisateur_idx ON jetons_usage_unique (utilisateur_id, objet);
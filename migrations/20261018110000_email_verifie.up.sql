@@ -0,0 +1,5 @@
+-- Vérification de l'adresse email à l'inscription
+ALTER TABLE utilisateur ADD COLUMN email_verifie BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Les comptes antérieurs à la vérification sont considérés comme vérifiés
+UPDATE utilisateur SET email_verifie = TRUE;
@@ -0,0 +1 @@
+DROP TABLE tentatives_connexion;
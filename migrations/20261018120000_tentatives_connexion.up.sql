@@ -0,0 +1,8 @@
+-- Table: Tentatives de connexion échouées
+-- cle = 'compte:<email>' ou 'ip:<adresse>'
+CREATE TABLE tentatives_connexion (
+    cle VARCHAR(300) PRIMARY KEY,
+    echecs INTEGER NOT NULL DEFAULT 0,
+    bloque_jusqu_a TIMESTAMPTZ,
+    derniere_tentative TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
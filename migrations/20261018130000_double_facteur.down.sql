@@ -0,0 +1,2 @@
+DROP TABLE codes_recuperation;
+DROP TABLE double_facteur;
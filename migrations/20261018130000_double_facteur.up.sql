@@ -0,0 +1,20 @@
+-- Table: Double facteur TOTP (RFC 6238)
+CREATE TABLE double_facteur (
+    utilisateur_id UUID PRIMARY KEY REFERENCES utilisateur(id),
+    secret VARCHAR(64) NOT NULL, -- octets du secret, en hexadécimal
+    actif BOOLEAN NOT NULL DEFAULT FALSE,
+    dernier_pas BIGINT, -- dernier pas de temps accepté (anti-rejeu)
+    date_activation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Table: Codes de récupération (hachés, usage unique)
+CREATE TABLE codes_recuperation (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    utilisateur_id UUID NOT NULL REFERENCES utilisateur(id),
+    code_hache VARCHAR(64) NOT NULL,
+    date_utilisation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX codes_recuperation_utilisateur_idx ON codes_recuperation (utilisateur_id);
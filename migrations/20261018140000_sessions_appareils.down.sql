@@ -0,0 +1,5 @@
+DROP TABLE jetons_rafraichissement;
+DROP INDEX sessions_utilisateur_idx;
+ALTER TABLE sessions DROP COLUMN derniere_activite;
+ALTER TABLE sessions DROP COLUMN adresse_ip;
+ALTER TABLE sessions DROP COLUMN appareil;
@@ -0,0 +1,18 @@
+-- Sessions par appareil : chaque connexion ouvre une session qui porte une famille de jetons de rafraîchissement
+ALTER TABLE sessions ADD COLUMN appareil VARCHAR(255);
+ALTER TABLE sessions ADD COLUMN adresse_ip VARCHAR(45);
+ALTER TABLE sessions ADD COLUMN derniere_activite TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX sessions_utilisateur_idx ON sessions (utilisateur_id);
+
+-- Table: Jetons de rafraîchissement (un seul utilisable par session, les anciens servent à détecter la réutilisation)
+-- Seul le SHA-256 du jeton est stocké
+CREATE TABLE jetons_rafraichissement (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    jeton_hache VARCHAR(64) NOT NULL UNIQUE,
+    date_utilisation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX jetons_rafraichissement_session_idx ON jetons_rafraichissement (session_id);
@@ -0,0 +1,2 @@
+DROP INDEX utilisateur_email_insensible_idx;
+ALTER TABLE utilisateur ADD CONSTRAINT utilisateur_email_key UNIQUE (email);
@@ -0,0 +1,5 @@
+-- Unicité de l'email sans tenir compte de la casse
+-- Prérequis : `web_site_rust normaliser-emails` ne signale plus aucun doublon,
+-- sinon la création de l'index échoue
+ALTER TABLE utilisateur DROP CONSTRAINT utilisateur_email_key;
+CREATE UNIQUE INDEX utilisateur_email_insensible_idx ON utilisateur (LOWER(email));
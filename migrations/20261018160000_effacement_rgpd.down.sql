@@ -0,0 +1 @@
+ALTER TABLE utilisateur DROP COLUMN date_effacement;
@@ -0,0 +1,2 @@
+-- Effacement RGPD : le compte est anonymisé plutôt que supprimé (les commandes le référencent)
+ALTER TABLE utilisateur ADD COLUMN date_effacement TIMESTAMPTZ;
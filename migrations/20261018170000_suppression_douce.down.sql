@@ -0,0 +1,2 @@
+DROP INDEX utilisateur_suppression_idx;
+ALTER TABLE utilisateur DROP COLUMN date_suppression;
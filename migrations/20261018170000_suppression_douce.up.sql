@@ -0,0 +1,4 @@
+-- Suppression douce des comptes, purgés après la durée de rétention
+ALTER TABLE utilisateur ADD COLUMN date_suppression TIMESTAMPTZ;
+
+CREATE INDEX utilisateur_suppression_idx ON utilisateur (date_suppression) WHERE date_suppression IS NOT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE journal_audit;
+DROP FUNCTION journal_audit_ajout_seul();
@@ -0,0 +1,31 @@
+-- Table: Journal d'audit des actions sur les comptes (ajout seul)
+-- Pas de clé étrangère : l'historique survit à la purge des comptes
+CREATE TABLE journal_audit (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    date TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    acteur_id UUID,
+    action VARCHAR(50) NOT NULL,
+    type_cible VARCHAR(30) NOT NULL,
+    cible_id UUID,
+    changements JSONB,
+    requete_id VARCHAR(64),
+    adresse_ip VARCHAR(45)
+);
+
+CREATE INDEX journal_audit_date_idx ON journal_audit (date DESC, id DESC);
+CREATE INDEX journal_audit_acteur_idx ON journal_audit (acteur_id, date DESC);
+CREATE INDEX journal_audit_cible_idx ON journal_audit (cible_id, date DESC);
+
+CREATE FUNCTION journal_audit_ajout_seul() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'journal_audit est en ajout seul';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER journal_audit_ajout_seul
+    BEFORE UPDATE OR DELETE ON journal_audit
+    FOR EACH ROW EXECUTE FUNCTION journal_audit_ajout_seul();
+
+CREATE TRIGGER journal_audit_sans_troncature
+    BEFORE TRUNCATE ON journal_audit
+    FOR EACH STATEMENT EXECUTE FUNCTION journal_audit_ajout_seul();
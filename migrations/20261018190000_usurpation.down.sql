@@ -0,0 +1,2 @@
+DROP INDEX journal_audit_usurpateur_idx;
+ALTER TABLE journal_audit DROP COLUMN usurpateur_id;
@@ -0,0 +1,5 @@
+-- Administrateur ayant agi en tant que l'acteur (usurpation d'identité)
+ALTER TABLE journal_audit ADD COLUMN usurpateur_id UUID;
+
+CREATE INDEX journal_audit_usurpateur_idx ON journal_audit (usurpateur_id, date DESC)
+    WHERE usurpateur_id IS NOT NULL;
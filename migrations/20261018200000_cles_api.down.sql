@@ -0,0 +1,7 @@
+DROP TABLE cles_api;
+
+UPDATE utilisateur SET role = 'client' WHERE role = 'service';
+
+ALTER TABLE utilisateur
+    DROP CONSTRAINT utilisateur_role_check,
+    ADD CONSTRAINT utilisateur_role_check CHECK (role IN ('client', 'personnel', 'admin'));
@@ -0,0 +1,20 @@
+-- Rôle des comptes techniques (ERP, entrepôt), authentifiés par clé d'API
+ALTER TABLE utilisateur
+    DROP CONSTRAINT utilisateur_role_check,
+    ADD CONSTRAINT utilisateur_role_check CHECK (role IN ('client', 'personnel', 'admin', 'service'));
+
+-- Table: Clés d'API des comptes de service (seul le hash SHA-256 est stocké)
+CREATE TABLE cles_api (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    utilisateur_id UUID NOT NULL REFERENCES utilisateur(id) ON DELETE CASCADE,
+    nom VARCHAR(100) NOT NULL,
+    prefixe VARCHAR(16) NOT NULL UNIQUE,
+    cle_hachee VARCHAR(64) NOT NULL UNIQUE,
+    portees JSONB NOT NULL DEFAULT '[]',
+    date_expiration TIMESTAMPTZ,
+    date_derniere_utilisation TIMESTAMPTZ,
+    date_revocation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX cles_api_utilisateur_idx ON cles_api (utilisateur_id);
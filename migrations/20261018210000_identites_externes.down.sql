@@ -0,0 +1,2 @@
+DROP TABLE demandes_oidc;
+DROP TABLE identites_externes;
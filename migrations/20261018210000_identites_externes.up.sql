@@ -0,0 +1,24 @@
+-- Table: Identités chez les fournisseurs OpenID Connect, rattachées à un compte
+CREATE TABLE identites_externes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    utilisateur_id UUID NOT NULL REFERENCES utilisateur(id) ON DELETE CASCADE,
+    fournisseur VARCHAR(50) NOT NULL,
+    -- Claim "sub" du fournisseur, stable contrairement à l'email
+    sujet VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    date_derniere_connexion TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (fournisseur, sujet)
+);
+
+CREATE INDEX identites_externes_utilisateur_idx ON identites_externes (utilisateur_id);
+
+-- Table: Demandes d'autorisation en cours (state, nonce et vérificateur PKCE), à usage unique
+CREATE TABLE demandes_oidc (
+    etat_hache VARCHAR(64) PRIMARY KEY,
+    fournisseur VARCHAR(50) NOT NULL,
+    verificateur_pkce VARCHAR(128) NOT NULL,
+    nonce VARCHAR(64) NOT NULL,
+    date_expiration TIMESTAMPTZ NOT NULL,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+DROP TABLE codes_sms;
+DROP TABLE telephones;
+
+DELETE FROM jetons_usage_unique WHERE objet = 'connexion_lien_magique';
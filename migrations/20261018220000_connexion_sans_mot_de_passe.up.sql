@@ -0,0 +1,26 @@
+-- Table: Numéro de téléphone d'un compte, utilisable pour la connexion par SMS une fois vérifié
+CREATE TABLE telephones (
+    utilisateur_id UUID PRIMARY KEY REFERENCES utilisateur(id) ON DELETE CASCADE,
+    -- Format E.164 (+33612345678)
+    numero VARCHAR(20) NOT NULL,
+    date_verification TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Un numéro vérifié ne désigne qu'un seul compte
+CREATE UNIQUE INDEX telephones_numero_verifie_idx ON telephones (numero) WHERE date_verification IS NOT NULL;
+
+-- Table: Codes à 6 chiffres envoyés par SMS (connexion, vérification du numéro)
+CREATE TABLE codes_sms (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    utilisateur_id UUID NOT NULL REFERENCES utilisateur(id) ON DELETE CASCADE,
+    objet VARCHAR(30) NOT NULL,
+    telephone VARCHAR(20) NOT NULL,
+    code_hache VARCHAR(64) NOT NULL,
+    essais INTEGER NOT NULL DEFAULT 0,
+    date_expiration TIMESTAMPTZ NOT NULL,
+    date_utilisation TIMESTAMPTZ,
+    date_creation TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX codes_sms_utilisateur_idx ON codes_sms (utilisateur_id, objet);
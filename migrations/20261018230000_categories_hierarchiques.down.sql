@@ -0,0 +1,2 @@
+DROP INDEX products_categorie_idx;
+ALTER TABLE categories DROP COLUMN parent_id;
@@ -0,0 +1,7 @@
+-- Arborescence des catégories : une catégorie non vide ne peut pas être supprimée
+ALTER TABLE categories
+    ADD COLUMN parent_id UUID REFERENCES categories(id) ON DELETE RESTRICT,
+    ADD CONSTRAINT categories_parent_distinct CHECK (parent_id <> id);
+
+CREATE INDEX categories_parent_idx ON categories (parent_id);
+CREATE INDEX products_categorie_idx ON products (categorie_id);
@@ -0,0 +1,2 @@
+-- Une remise fixe est exprimée dans une devise, comme le total des commandes
+ALTER TABLE promotions ADD COLUMN devise VARCHAR(3) NOT NULL DEFAULT 'EUR';
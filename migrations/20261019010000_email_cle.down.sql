@@ -0,0 +1,3 @@
+DROP INDEX utilisateur_email_cle_idx;
+CREATE UNIQUE INDEX utilisateur_email_insensible_idx ON utilisateur (LOWER(email));
+ALTER TABLE utilisateur DROP COLUMN email_cle;
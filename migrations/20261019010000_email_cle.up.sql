@@ -0,0 +1,9 @@
+-- Clé d'unicité et de recherche de l'email, calculée par l'application
+-- (minuscules, variantes des fournisseurs de EMAIL_REGLES_FOURNISSEURS ramenées à une seule)
+-- L'initialisation en minuscules reproduit l'index précédent ; relancer
+-- `web_site_rust normaliser-emails --appliquer` après tout changement de règles
+ALTER TABLE utilisateur ADD COLUMN email_cle VARCHAR(255);
+UPDATE utilisateur SET email_cle = LOWER(email);
+ALTER TABLE utilisateur ALTER COLUMN email_cle SET NOT NULL;
+DROP INDEX utilisateur_email_insensible_idx;
+CREATE UNIQUE INDEX utilisateur_email_cle_idx ON utilisateur (email_cle);
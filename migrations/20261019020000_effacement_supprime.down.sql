@@ -0,0 +1,2 @@
+-- Rien à annuler : la date de suppression des comptes effacés reste renseignée
+SELECT 1;
@@ -0,0 +1,3 @@
+-- Un compte effacé (anonymisé) est aussi supprimé : les lectures l'ignorent
+UPDATE utilisateur SET date_suppression = date_effacement
+WHERE date_effacement IS NOT NULL AND date_suppression IS NULL;
@@ -0,0 +1,4 @@
+-- Session propre à chaque usurpation d'identité : la fermer révoque le jeton, y compris après un redémarrage ;
+-- la session de l'administrateur dont elle dépend l'emporte avec elle
+ALTER TABLE sessions ADD COLUMN session_parente_id UUID REFERENCES sessions(id) ON DELETE CASCADE;
+CREATE INDEX sessions_parente_idx ON sessions (session_parente_id);
@@ -0,0 +1,2 @@
+-- Les valeurs masquées ne peuvent pas être restituées
+SELECT 1;
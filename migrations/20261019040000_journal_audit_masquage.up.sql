@@ -0,0 +1,24 @@
+-- Masque les données personnelles déjà consignées : le journal ne garde que le nom des champs modifiés
+-- Seule exception au trigger d'ajout seul, le temps de cette migration
+ALTER TABLE journal_audit DISABLE TRIGGER journal_audit_ajout_seul;
+
+UPDATE journal_audit j
+SET changements = (
+    SELECT jsonb_object_agg(
+        champ,
+        CASE
+            WHEN champ IN ('email', 'prenom', 'nom', 'telephone') AND jsonb_typeof(valeur) = 'object' THEN (
+                SELECT jsonb_object_agg(cle, CASE WHEN v = 'null'::jsonb THEN v ELSE '"***"'::jsonb END)
+                FROM jsonb_each(valeur) AS c(cle, v)
+            )
+            WHEN champ IN ('telephone', 'sujet') THEN '"***"'::jsonb
+            ELSE valeur
+        END
+    )
+    FROM jsonb_each(j.changements) AS e(champ, valeur)
+)
+WHERE type_cible = 'utilisateur'
+  AND jsonb_typeof(changements) = 'object'
+  AND changements ?| ARRAY['email', 'prenom', 'nom', 'telephone', 'sujet'];
+
+ALTER TABLE journal_audit ENABLE TRIGGER journal_audit_ajout_seul;
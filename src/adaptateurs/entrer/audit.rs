@@ -0,0 +1,143 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use futures_util::future::{ready, Ready};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::ports::audit::AuditPort;
+use crate::domain::audit::{ActionAudit, EntreeAudit, RequeteAudit};
+use crate::domain::error::MyError;
+use crate::domain::role::Permission;
+use crate::adaptateurs::entrer::auth::UtilisateurAuthentifie;
+
+pub const EN_TETE_REQUETE: &str = "X-Request-Id";
+// Présent sur toute réponse à un jeton d'usurpation, avec l'id de l'administrateur
+pub const EN_TETE_USURPATION: &str = "x-usurpation";
+
+
+// Identifiant de corrélation de la requête en cours, repris dans le journal d'audit
+#[derive(Debug, Clone)]
+pub struct IdentifiantRequete(pub String);
+
+// Déposé par UtilisateurAuthentifie quand le jeton est un jeton d'usurpation
+#[derive(Debug, Clone, Copy)]
+pub struct Usurpation {
+    pub utilisateur_id: Uuid,
+    pub usurpateur_id: Uuid,
+}
+
+// Identifiant fourni par un proxy amont, s'il est raisonnable ; sinon un nouvel UUID
+fn identifiant_fourni(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(EN_TETE_REQUETE)
+        .and_then(|valeur| valeur.to_str().ok())
+        .map(str::trim)
+        .filter(|valeur| {
+            !valeur.is_empty()
+                && valeur.len() <= 64
+                && valeur.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+        .map(str::to_string)
+}
+
+// Middleware : attribue un X-Request-Id à chaque requête et le renvoie dans la réponse
+pub async fn identifiant_requete(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let identifiant = identifiant_fourni(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(IdentifiantRequete(identifiant.clone()));
+
+    let mut reponse = next.call(req).await?;
+    if let Ok(valeur) = HeaderValue::from_str(&identifiant) {
+        reponse.headers_mut().insert(HeaderName::from_static("x-request-id"), valeur);
+    }
+    Ok(reponse)
+}
+
+// Middleware : signale et journalise chaque requête faite sous usurpation d'identité
+pub async fn journaliser_usurpation(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let audit = req.app_data::<web::Data<dyn AuditPort>>().cloned();
+    let (methode, chemin) = (req.method().to_string(), req.path().to_string());
+
+    let mut reponse = next.call(req).await?;
+    let usurpation = reponse.request().extensions().get::<Usurpation>().copied();
+    if let Some(usurpation) = usurpation {
+        if let Ok(valeur) = HeaderValue::from_str(&usurpation.usurpateur_id.to_string()) {
+            reponse.headers_mut().insert(HeaderName::from_static(EN_TETE_USURPATION), valeur);
+        }
+        if let Some(audit) = audit {
+            let entree = EntreeAudit::new(
+                ActionAudit::RequeteUsurpee,
+                Some(usurpation.utilisateur_id),
+                Some(usurpation.utilisateur_id),
+            )
+            .avec_changements(json!({
+                "methode": methode,
+                "chemin": chemin,
+                "statut": reponse.status().as_u16(),
+            }));
+            Auditeur { audit, req: reponse.request().clone() }.consigner(entree).await;
+        }
+    }
+    Ok(reponse)
+}
+
+// Extracteur : consigne les actions de la requête en cours dans le journal d'audit
+pub struct Auditeur {
+    audit: web::Data<dyn AuditPort>,
+    req: HttpRequest,
+}
+
+impl FromRequest for Auditeur {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.app_data::<web::Data<dyn AuditPort>>()
+                .cloned()
+                .ok_or_else(|| MyError::Custom("AuditPort non configuré".to_string()))
+                .map(|audit| Auditeur { audit, req: req.clone() }),
+        )
+    }
+}
+
+impl Auditeur {
+    // L'action a déjà eu lieu : un échec d'écriture du journal est tracé mais ne fait pas échouer la requête
+    pub async fn consigner(&self, mut entree: EntreeAudit) {
+        // Lus au moment de consigner : l'authentification a pu avoir lieu après l'extraction
+        {
+            let extensions = self.req.extensions();
+            entree.requete_id = extensions.get::<IdentifiantRequete>().map(|id| id.0.clone());
+            entree.usurpateur_id = extensions.get::<Usurpation>().map(|usurpation| usurpation.usurpateur_id);
+        }
+        entree.adresse_ip = self.req.peer_addr().map(|adresse| adresse.ip().to_string());
+        if let Err(e) = self.audit.enregistrer(&entree).await {
+            tracing::error!("Entrée d'audit {:?} non enregistrée: {}", entree.action, e);
+        }
+    }
+}
+
+pub async fn lister(
+    auth: UtilisateurAuthentifie,
+    audit: web::Data<dyn AuditPort>,
+    requete: web::Query<RequeteAudit>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::LireAudit)?;
+    Ok(HttpResponse::Ok().json(audit.lister(&requete).await?))
+}
+
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/audit")
+            .route("", web::get().to(lister))
+    );
+}
@@ -0,0 +1,1165 @@
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::cookie::{time::Duration as DureeCookie, Cookie, SameSite};
+use chrono::{DateTime, Utc};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::ports::users::UtilisateurEntree;
+use crate::ports::sessions::SessionPort;
+use crate::ports::jetons::JetonPort;
+use crate::ports::mail::MailPort;
+use crate::ports::tentatives::TentativesPort;
+use crate::ports::double_facteur::DoubleFacteurPort;
+use crate::ports::cles_api::ClesApiPort;
+use crate::ports::oidc::{FournisseurOidcPort, IdentitesExternesPort};
+use crate::ports::telephones::TelephonePort;
+use crate::ports::sms::SmsPort;
+use std::sync::Arc;
+
+use crate::domain::auth::{
+    Claims, ConfirmationEmail, Connexion, ConnexionLienMagique, DemandeLienMagique, DemandeReinitialisation,
+    Rafraichissement, Reinitialisation, ServiceJwt, TypeJeton,
+};
+use crate::domain::models::Session;
+use crate::domain::session::{ConfigSession, JetonRafraichissement, Rotation, SessionActive};
+use crate::domain::verification::{ActionRestreinte, RestrictionsEmailNonVerifie};
+use crate::domain::tentatives::{
+    cle_compte, cle_envoi, cle_ip, cle_verification_telephone, est_cle_compte, PolitiqueTentatives,
+};
+use crate::domain::totp::{
+    generer_codes_recuperation, normaliser_code_recuperation, CodeDoubleFacteur, CodesRecuperation, ConfigTotp,
+    DoubleFacteur, InscriptionDoubleFacteur, VerificationDoubleFacteur,
+};
+use crate::domain::courriel::Courriel;
+use crate::domain::jeton::{hacher_jeton, ConfigJetons, JetonUsageUnique, ObjetJeton};
+use crate::domain::user::Utilisateur;
+use crate::domain::error::MyError;
+use crate::domain::concurrence::Precondition;
+use crate::domain::email::{normaliser_email, ReglesEmail};
+use crate::domain::mot_de_passe::HacheurMotDePasse;
+use crate::domain::role::{Permission, Role};
+use crate::domain::usurpation::ActionSensible;
+use crate::domain::sms::{
+    normaliser_telephone, ChangementTelephone, CodeSms, ConfirmationTelephone, ConnexionSms, DemandeCodeSms,
+    ObjetCodeSms, Sms,
+};
+use crate::domain::oidc::{DemandeOidc, IdentiteExterne, ProfilOidc, RetourOidc};
+use crate::domain::audit::{changements_utilisateur, ActionAudit, EntreeAudit, MASQUE};
+use crate::adaptateurs::entrer::sessions::token_de_session;
+use crate::adaptateurs::entrer::audit::{Auditeur, Usurpation};
+
+
+// Extracteur : utilisateur porteur d'un jeton d'accès valide (Authorization: Bearer ...)
+// ou compte de service porteur d'une clé d'API (Authorization: ApiKey ...)
+pub struct UtilisateurAuthentifie {
+    pub utilisateur: Utilisateur,
+    pub claims: Claims,
+    // Portées de la clé d'API utilisée ; None pour un jeton JWT
+    pub portees: Option<Vec<Permission>>,
+}
+
+// Clé d'API d'un compte de service : mêmes claims qu'un jeton d'accès, jti = id de la clé
+async fn authentifier_cle_api(
+    req: &HttpRequest,
+    repo: &dyn UtilisateurEntree,
+    cle: &str,
+) -> Result<UtilisateurAuthentifie, MyError> {
+    let cles = req
+        .app_data::<web::Data<dyn ClesApiPort>>()
+        .ok_or_else(|| MyError::Custom("ClesApiPort non configuré".to_string()))?;
+    let cle = cles
+        .obtenir_valide(&hacher_jeton(cle))
+        .await?
+        .ok_or_else(|| MyError::Unauthorized("Clé d'API invalide, révoquée ou expirée".to_string()))?;
+    // Une clé ne sert plus si le compte a changé de rôle ou a été supprimé
+    let utilisateur = repo
+        .obtenir_par_id(cle.utilisateur_id)
+        .await?
+        .filter(|utilisateur| utilisateur.role == Role::Service)
+        .ok_or_else(|| MyError::Unauthorized("Clé d'API invalide, révoquée ou expirée".to_string()))?;
+    if let Err(e) = cles.marquer_utilisation(cle.id).await {
+        tracing::error!("Dernière utilisation de la clé {} non enregistrée: {}", cle.id, e);
+    }
+
+    let now = Utc::now();
+    let claims = Claims {
+        sub: utilisateur.id,
+        role: utilisateur.role,
+        typ: TypeJeton::Acces,
+        jti: cle.id,
+        sid: None,
+        usurpateur: None,
+        iat: now.timestamp(),
+        exp: cle.date_expiration.map_or(i64::MAX, |expiration| expiration.timestamp()),
+    };
+    Ok(UtilisateurAuthentifie { utilisateur, claims, portees: Some(cle.portees.0) })
+}
+
+impl FromRequest for UtilisateurAuthentifie {
+    type Error = MyError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let jwt = req
+                .app_data::<web::Data<ServiceJwt>>()
+                .ok_or_else(|| MyError::Custom("ServiceJwt non configuré".to_string()))?;
+            let repo = req
+                .app_data::<web::Data<dyn UtilisateurEntree>>()
+                .ok_or_else(|| MyError::Custom("Repository non configuré".to_string()))?;
+
+            let autorisation = req
+                .headers()
+                .get("Authorization")
+                .and_then(|valeur| valeur.to_str().ok())
+                .unwrap_or_default();
+            if let Some(cle) = autorisation.strip_prefix("ApiKey ") {
+                return authentifier_cle_api(&req, repo.get_ref(), cle).await;
+            }
+            let jeton = autorisation
+                .strip_prefix("Bearer ")
+                .ok_or_else(|| MyError::Unauthorized("Jeton d'accès manquant".to_string()))?;
+
+            let claims = jwt.valider(jeton, TypeJeton::Acces)?;
+            if let Some(sid) = claims.sid {
+                let sessions = req
+                    .app_data::<web::Data<dyn SessionPort>>()
+                    .ok_or_else(|| MyError::Custom("SessionPort non configuré".to_string()))?;
+                let session = sessions.obtenir_par_id(sid).await?;
+                // Sous usurpation, la session est celle de l'administrateur
+                let titulaire = claims.usurpateur.unwrap_or(claims.sub);
+                if session.and_then(|session| session.utilisateur_id) != Some(titulaire) {
+                    return Err(MyError::Unauthorized("Session révoquée".to_string()));
+                }
+            }
+            if let Some(usurpateur_id) = claims.usurpateur {
+                // L'administrateur doit toujours exister et avoir le droit d'usurper
+                let autorise = repo
+                    .obtenir_par_id(usurpateur_id)
+                    .await?
+                    .is_some_and(|usurpateur| usurpateur.role.a_permission(Permission::UsurperIdentite));
+                if !autorise {
+                    return Err(MyError::Unauthorized("Usurpation révoquée".to_string()));
+                }
+                req.extensions_mut().insert(Usurpation { utilisateur_id: claims.sub, usurpateur_id });
+            }
+            let utilisateur = repo
+                .obtenir_par_id(claims.sub)
+                .await?
+                .ok_or_else(|| MyError::Unauthorized("Utilisateur inconnu".to_string()))?;
+
+            Ok(UtilisateurAuthentifie { utilisateur, claims, portees: None })
+        })
+    }
+}
+
+impl UtilisateurAuthentifie {
+    // Permission du rôle et, avec une clé d'API, portée de la clé
+    pub fn exiger(&self, permission: Permission) -> Result<(), MyError> {
+        let dans_portee = self.portees.as_ref().is_none_or(|portees| portees.contains(&permission));
+        if self.utilisateur.role.a_permission(permission) && dans_portee {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden("Droits insuffisants".to_string()))
+        }
+    }
+
+    pub fn interdire_en_usurpation(&self, action: ActionSensible) -> Result<(), MyError> {
+        match self.claims.usurpateur {
+            Some(_) => Err(action.refus()),
+            None => Ok(()),
+        }
+    }
+
+    // Accès à son propre compte, ou à celui d'un autre avec la permission donnée
+    // Une clé d'API reste limitée à ses portées, même sur son propre compte
+    pub fn exiger_soi_ou(&self, cible: Uuid, permission: Permission) -> Result<(), MyError> {
+        if self.utilisateur.id == cible && self.portees.is_none() {
+            Ok(())
+        } else {
+            self.exiger(permission)
+        }
+    }
+}
+
+// Donnée partagée de l'application, enregistrée dans main
+pub fn donnee_app<T: ?Sized + 'static>(req: &HttpRequest, nom: &str) -> Result<web::Data<T>, MyError> {
+    req.app_data::<web::Data<T>>()
+        .cloned()
+        .ok_or_else(|| MyError::Custom(format!("{} non configuré", nom)))
+}
+
+// Extracteur : dépendances communes à toutes les méthodes de connexion
+pub struct ServicesConnexion {
+    pub repo: web::Data<dyn UtilisateurEntree>,
+    pub sessions: web::Data<dyn SessionPort>,
+    pub tentatives: web::Data<dyn TentativesPort>,
+    pub double_facteur: web::Data<dyn DoubleFacteurPort>,
+    pub politique: web::Data<PolitiqueTentatives>,
+    pub config_session: web::Data<ConfigSession>,
+    pub hacheur: web::Data<HacheurMotDePasse>,
+    pub jwt: web::Data<ServiceJwt>,
+    pub restrictions: web::Data<RestrictionsEmailNonVerifie>,
+    pub regles_email: web::Data<ReglesEmail>,
+}
+
+impl FromRequest for ServicesConnexion {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let services = || -> Result<Self, MyError> {
+            Ok(ServicesConnexion {
+                repo: donnee_app(req, "Repository")?,
+                sessions: donnee_app(req, "SessionPort")?,
+                tentatives: donnee_app(req, "TentativesPort")?,
+                double_facteur: donnee_app(req, "DoubleFacteurPort")?,
+                politique: donnee_app(req, "PolitiqueTentatives")?,
+                config_session: donnee_app(req, "ConfigSession")?,
+                hacheur: donnee_app(req, "HacheurMotDePasse")?,
+                jwt: donnee_app(req, "ServiceJwt")?,
+                restrictions: donnee_app(req, "RestrictionsEmailNonVerifie")?,
+                regles_email: donnee_app(req, "ReglesEmail")?,
+            })
+        };
+        ready(services())
+    }
+}
+
+// Émet un jeton de vérification et envoie le lien à l'adresse actuelle du compte
+pub async fn demander_verification_email(
+    utilisateur: &Utilisateur,
+    jetons: &dyn JetonPort,
+    mail: Arc<dyn MailPort>,
+    config: &ConfigJetons,
+) -> Result<(), MyError> {
+    jetons.revoquer_tous(utilisateur.id, ObjetJeton::VerificationEmail).await?;
+    let (clair, jeton) = JetonUsageUnique::new(utilisateur.id, ObjetJeton::VerificationEmail, config.duree_verification);
+    jetons.creer(&jeton).await?;
+
+    let lien = format!("{}/confirmer-email?jeton={}", config.url_application, clair);
+    let courriel = Courriel::verification_email(&utilisateur.email, &lien, config.duree_verification);
+    actix_web::rt::spawn(async move {
+        if let Err(e) = mail.envoyer(&courriel).await {
+            tracing::error!("Mail de vérification non envoyé: {}", e);
+        }
+    });
+    Ok(())
+}
+
+// Clés de suivi des échecs pour une tentative : compte visé et adresse IP
+fn cles_tentative(req: &HttpRequest, regles: &ReglesEmail, email: &str) -> Vec<String> {
+    let mut cles = vec![cle_compte(email, regles)];
+    if let Some(adresse) = req.peer_addr() {
+        cles.push(cle_ip(adresse.ip()));
+    }
+    cles
+}
+
+pub async fn verifier_tentatives(
+    tentatives: &dyn TentativesPort,
+    politique: &PolitiqueTentatives,
+    cles: &[String],
+) -> Result<(), MyError> {
+    let maintenant = Utc::now();
+    for cle in cles {
+        if let Some(etat) = tentatives.obtenir(cle).await? {
+            politique.verifier(&etat, maintenant)?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn enregistrer_echec(
+    tentatives: &dyn TentativesPort,
+    politique: &PolitiqueTentatives,
+    cles: &[String],
+) -> Result<(), MyError> {
+    let maintenant = Utc::now();
+    for cle in cles {
+        let echecs = tentatives.incrementer(cle, politique.fenetre_oubli).await?;
+        if let Some(jusqu_a) = politique.blocage_apres(echecs, est_cle_compte(cle), maintenant) {
+            tentatives.bloquer(cle, jusqu_a).await?;
+        }
+    }
+    Ok(())
+}
+
+// Renvoie l'utilisateur et, si le coût bcrypt a changé, son nouveau hash
+async fn authentifier(
+    repo: &dyn UtilisateurEntree,
+    hacheur: &HacheurMotDePasse,
+    identifiants: &Connexion,
+) -> Result<(Utilisateur, Option<String>), MyError> {
+    let Some(utilisateur) = repo.obtenir_par_email(&normaliser_email(&identifiants.email)).await? else {
+        hacheur.verifier_factice(&identifiants.mot_de_passe).await?;
+        return Err(MyError::Unauthorized("Identifiants invalides".to_string()));
+    };
+    let nouveau_hache = hacheur
+        .verifier_et_rehacher(&identifiants.mot_de_passe, &utilisateur.mot_de_passe)
+        .await?;
+    Ok((utilisateur, nouveau_hache))
+}
+
+// Appareil (User-Agent) et adresse IP, affichés dans la liste des sessions
+fn origine(req: &HttpRequest) -> (Option<String>, Option<String>) {
+    let appareil = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|valeur| valeur.to_str().ok())
+        .map(|valeur| valeur.chars().take(255).collect());
+    let adresse_ip = req.peer_addr().map(|adresse| adresse.ip().to_string());
+    (appareil, adresse_ip)
+}
+
+// Dernière étape commune : reprise de la session invitée, ouverture d'une session d'appareil et émission des jetons
+async fn finaliser_connexion(
+    req: &HttpRequest,
+    services: &ServicesConnexion,
+    utilisateur: &Utilisateur,
+) -> Result<HttpResponse, MyError> {
+    let sessions = services.sessions.get_ref();
+    // Une session invitée (panier) en cours est reprise par le compte
+    if let Some(token) = token_de_session(req)
+        && let Some(session) = sessions.obtenir_par_token(&token).await?
+        && session.utilisateur_id.is_none()
+    {
+        sessions.rattacher(&token, utilisateur.id).await?;
+    }
+
+    let (appareil, adresse_ip) = origine(req);
+    let session = Session::appareil(utilisateur.id, services.config_session.duree_rafraichissement, appareil, adresse_ip);
+    let session = sessions.creer(&session).await?;
+    let (clair, jeton) = JetonRafraichissement::new(session.id);
+    sessions.ajouter_rafraichissement(&jeton).await?;
+
+    Ok(HttpResponse::Ok().json(services.jwt.emettre(utilisateur, session.id, clair)?))
+}
+
+// Code TOTP (anti-rejeu par pas de temps) ou, à défaut, code de récupération
+async fn verifier_code(
+    double_facteur: &dyn DoubleFacteurPort,
+    config: &ConfigTotp,
+    facteur: &DoubleFacteur,
+    code: &str,
+) -> Result<bool, MyError> {
+    if let Some(pas) = config.verifier(facteur, code, Utc::now()) {
+        return double_facteur.marquer_pas(facteur.utilisateur_id, pas).await;
+    }
+    let code_hache = hacher_jeton(&normaliser_code_recuperation(code));
+    double_facteur.consommer_code_recuperation(facteur.utilisateur_id, &code_hache).await
+}
+
+async fn facteur_actif(double_facteur: &dyn DoubleFacteurPort, utilisateur_id: Uuid) -> Result<Option<DoubleFacteur>, MyError> {
+    Ok(double_facteur.obtenir(utilisateur_id).await?.filter(|facteur| facteur.actif))
+}
+
+// Fin commune à toutes les méthodes, une fois le premier facteur validé (mot de passe, OIDC, lien magique, SMS)
+async fn conclure_connexion(
+    req: &HttpRequest,
+    auditeur: &Auditeur,
+    services: &ServicesConnexion,
+    utilisateur: &Utilisateur,
+    methode: &str,
+) -> Result<HttpResponse, MyError> {
+    services.restrictions.verifier(utilisateur, ActionRestreinte::Connexion)?;
+    if utilisateur.role == Role::Service {
+        return Err(MyError::Forbidden("Compte de service : authentification par clé d'API uniquement".to_string()));
+    }
+    // Le second facteur reste exigé, quel que soit le premier ; les échecs du compte
+    // ne sont remis à zéro qu'une fois le second facteur validé
+    if facteur_actif(services.double_facteur.get_ref(), utilisateur.id).await?.is_some() {
+        return Ok(HttpResponse::Ok().json(services.jwt.emettre_deux_facteurs(utilisateur)?));
+    }
+    services.tentatives.effacer(&cle_compte(&utilisateur.email, &services.regles_email)).await?;
+
+    let reponse = finaliser_connexion(req, services, utilisateur).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::Connexion, Some(utilisateur.id), Some(utilisateur.id))
+            .avec_changements(serde_json::json!({ "methode": methode })),
+    ).await;
+    Ok(reponse)
+}
+
+pub async fn connexion(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    identifiants: web::Json<Connexion>,
+) -> Result<HttpResponse, MyError> {
+    let identifiants = identifiants.into_inner();
+    let tentatives = services.tentatives.get_ref();
+    let cles = cles_tentative(&req, &services.regles_email, &identifiants.email);
+    verifier_tentatives(tentatives, &services.politique, &cles).await?;
+
+    let repo = services.repo.get_ref();
+    let (mut utilisateur, nouveau_hache) = match authentifier(repo, &services.hacheur, &identifiants).await {
+        Err(MyError::Unauthorized(message)) => {
+            enregistrer_echec(tentatives, &services.politique, &cles).await?;
+            return Err(MyError::Unauthorized(message));
+        }
+        resultat => resultat?,
+    };
+
+    // Rehachage transparent si le coût bcrypt a changé depuis l'enregistrement
+    if let Some(nouveau_hache) = nouveau_hache {
+        utilisateur.mot_de_passe = nouveau_hache;
+        utilisateur = repo.mettre_a_jour(&utilisateur, &Precondition::Toute).await?;
+    }
+    conclure_connexion(&req, &auditeur, &services, &utilisateur, "mot_de_passe").await
+}
+
+pub async fn verifier_deux_facteurs(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    config: web::Data<ConfigTotp>,
+    jetons: web::Data<dyn JetonPort>,
+    corps: web::Json<VerificationDoubleFacteur>,
+) -> Result<HttpResponse, MyError> {
+    let claims = services.jwt.valider(&corps.jeton_deux_facteurs, TypeJeton::DeuxFacteurs)?;
+    let utilisateur = services
+        .repo
+        .obtenir_par_id(claims.sub)
+        .await?
+        .ok_or_else(|| MyError::Unauthorized("Utilisateur inconnu".to_string()))?;
+    let (tentatives, double_facteur) = (services.tentatives.get_ref(), services.double_facteur.get_ref());
+    let cles = cles_tentative(&req, &services.regles_email, &utilisateur.email);
+    verifier_tentatives(tentatives, &services.politique, &cles).await?;
+
+    let facteur = facteur_actif(double_facteur, utilisateur.id)
+        .await?
+        .ok_or_else(|| MyError::Unauthorized("Double facteur non activé".to_string()))?;
+    if !verifier_code(double_facteur, &config, &facteur, &corps.code).await? {
+        enregistrer_echec(tentatives, &services.politique, &cles).await?;
+        return Err(MyError::Unauthorized("Code invalide".to_string()));
+    }
+
+    // Le jeton intermédiaire ne sert qu'une fois, y compris d'une instance ou d'un redémarrage à l'autre
+    let expiration = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+    let jeton = JetonUsageUnique::utilise(utilisateur.id, ObjetJeton::DeuxFacteurs, &claims.jti.to_string(), expiration);
+    if !jetons.marquer_utilise(&jeton).await? {
+        return Err(MyError::Unauthorized("Jeton révoqué".to_string()));
+    }
+    tentatives.effacer(&cle_compte(&utilisateur.email, &services.regles_email)).await?;
+    let reponse = finaliser_connexion(&req, &services, &utilisateur).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::Connexion, Some(utilisateur.id), Some(utilisateur.id))
+            .avec_changements(serde_json::json!({ "deux_facteurs": true })),
+    ).await;
+    Ok(reponse)
+}
+
+// Nouveau secret en attente : remplace une inscription non confirmée
+pub async fn inscrire_deux_facteurs(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    double_facteur: web::Data<dyn DoubleFacteurPort>,
+    config: web::Data<ConfigTotp>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::DoubleFacteur)?;
+    if facteur_actif(double_facteur.get_ref(), auth.utilisateur.id).await?.is_some() {
+        return Err(MyError::BadRequest("Double facteur déjà activé".to_string()));
+    }
+    let facteur = DoubleFacteur::new(auth.utilisateur.id);
+    double_facteur.enregistrer(&facteur).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::InscriptionDoubleFacteur, Some(auth.utilisateur.id), Some(auth.utilisateur.id)),
+    ).await;
+
+    Ok(HttpResponse::Created().json(InscriptionDoubleFacteur {
+        secret: facteur.secret_base32(),
+        uri: config.uri(&facteur, &auth.utilisateur.email),
+    }))
+}
+
+// Active le second facteur après un premier code valide ; les codes de récupération ne sont montrés qu'ici
+pub async fn confirmer_deux_facteurs(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    double_facteur: web::Data<dyn DoubleFacteurPort>,
+    config: web::Data<ConfigTotp>,
+    corps: web::Json<CodeDoubleFacteur>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::DoubleFacteur)?;
+    let facteur = double_facteur
+        .obtenir(auth.utilisateur.id)
+        .await?
+        .ok_or_else(|| MyError::BadRequest("Aucune inscription en attente".to_string()))?;
+    if facteur.actif {
+        return Err(MyError::BadRequest("Double facteur déjà activé".to_string()));
+    }
+    let pas = config
+        .verifier(&facteur, &corps.code, Utc::now())
+        .ok_or_else(|| MyError::BadRequest("Code invalide".to_string()))?;
+
+    let codes = generer_codes_recuperation();
+    let codes_haches: Vec<String> = codes
+        .iter()
+        .map(|code| hacher_jeton(&normaliser_code_recuperation(code)))
+        .collect();
+    double_facteur.activer(auth.utilisateur.id, pas, &codes_haches).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::ActivationDoubleFacteur, Some(auth.utilisateur.id), Some(auth.utilisateur.id)),
+    ).await;
+
+    Ok(HttpResponse::Ok().json(CodesRecuperation { codes_recuperation: codes }))
+}
+
+pub async fn desactiver_deux_facteurs(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    double_facteur: web::Data<dyn DoubleFacteurPort>,
+    config: web::Data<ConfigTotp>,
+    corps: web::Json<CodeDoubleFacteur>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::DoubleFacteur)?;
+    let facteur = facteur_actif(double_facteur.get_ref(), auth.utilisateur.id)
+        .await?
+        .ok_or_else(|| MyError::BadRequest("Double facteur non activé".to_string()))?;
+    if !verifier_code(double_facteur.get_ref(), &config, &facteur, &corps.code).await? {
+        return Err(MyError::Unauthorized("Code invalide".to_string()));
+    }
+    double_facteur.desactiver(auth.utilisateur.id).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::DesactivationDoubleFacteur, Some(auth.utilisateur.id), Some(auth.utilisateur.id)),
+    ).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Cookie qui lie le state au navigateur ayant lancé la connexion (contre la connexion forcée)
+const COOKIE_ETAT_OIDC: &str = "oidc_etat";
+const CHEMIN_COOKIE_ETAT_OIDC: &str = "/auth/oidc";
+
+// Connexion OpenID Connect (code d'autorisation + PKCE) : redirection vers le fournisseur,
+// state, nonce et vérificateur PKCE restant côté serveur
+pub async fn autoriser_oidc(
+    req: HttpRequest,
+    path: web::Path<String>,
+    oidc: web::Data<dyn FournisseurOidcPort>,
+    identites: web::Data<dyn IdentitesExternesPort>,
+) -> Result<HttpResponse, MyError> {
+    let (etat, demande) = DemandeOidc::new(&path.into_inner());
+    // NotFound pour un fournisseur inconnu, avant tout enregistrement
+    let url = oidc.url_autorisation(&demande, &etat).await?;
+    identites.creer_demande(&demande).await?;
+    let duree = (demande.date_expiration - demande.date_creation).num_seconds();
+    let cookie = Cookie::build(COOKIE_ETAT_OIDC, etat)
+        .path(CHEMIN_COOKIE_ETAT_OIDC)
+        .http_only(true)
+        .secure(req.connection_info().scheme() == "https")
+        // Lax : le cookie accompagne la redirection de haut niveau depuis le fournisseur
+        .same_site(SameSite::Lax)
+        .max_age(DureeCookie::seconds(duree))
+        .finish();
+    Ok(HttpResponse::Found().insert_header((header::LOCATION, url)).cookie(cookie).finish())
+}
+
+// Compte de l'identité externe : par son sujet, sinon par l'email vérifié (liaison ou création)
+async fn compte_oidc(
+    fournisseur: &str,
+    profil: &ProfilOidc,
+    identites: &dyn IdentitesExternesPort,
+    repo: &dyn UtilisateurEntree,
+    hacheur: &HacheurMotDePasse,
+) -> Result<(Utilisateur, Vec<EntreeAudit>), MyError> {
+    if let Some(identite) = identites.obtenir(fournisseur, &profil.sujet).await? {
+        identites.toucher(identite.id).await?;
+        let utilisateur = repo
+            .obtenir_par_id(identite.utilisateur_id)
+            .await?
+            .ok_or_else(|| MyError::Unauthorized("Utilisateur inconnu".to_string()))?;
+        return Ok((utilisateur, Vec::new()));
+    }
+
+    // Sans vérification par le fournisseur, l'email ne prouve pas la possession du compte local
+    let email = profil
+        .email
+        .as_deref()
+        .filter(|_| profil.email_verifie)
+        .map(normaliser_email)
+        .ok_or_else(|| MyError::Forbidden("Email non vérifié par le fournisseur d'identité".to_string()))?;
+
+    let mut entrees = Vec::new();
+    let utilisateur = match repo.obtenir_par_email(&email).await? {
+        // Un compte jamais vérifié a pu être créé par un tiers avec cette adresse : le lier lui
+        // laisserait un mot de passe valide sur le compte de la personne qui se connecte
+        Some(utilisateur) if !utilisateur.email_verifie => {
+            return Err(MyError::Forbidden(
+                "Un compte non vérifié utilise cette adresse : réinitialisez son mot de passe et confirmez l'adresse avant de vous connecter avec ce fournisseur".to_string(),
+            ));
+        }
+        Some(utilisateur) => utilisateur,
+        None => {
+            let utilisateur = repo.creer(&profil.nouveau_compte(email, hacheur).await?).await?;
+            entrees.push(
+                EntreeAudit::new(ActionAudit::CreationUtilisateur, Some(utilisateur.id), Some(utilisateur.id))
+                    .avec_changements(changements_utilisateur(None, Some(&utilisateur))),
+            );
+            utilisateur
+        }
+    };
+    identites.lier(&IdentiteExterne::new(utilisateur.id, fournisseur, profil)).await?;
+    entrees.push(
+        EntreeAudit::new(ActionAudit::LiaisonIdentiteExterne, Some(utilisateur.id), Some(utilisateur.id))
+            .avec_changements(serde_json::json!({ "fournisseur": fournisseur, "sujet": MASQUE })),
+    );
+    Ok((utilisateur, entrees))
+}
+
+// Retour du fournisseur sur l'URL de redirection : échange du code, puis même fin que la connexion par mot de passe
+pub async fn retour_oidc(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    path: web::Path<String>,
+    retour: web::Query<RetourOidc>,
+    oidc: web::Data<dyn FournisseurOidcPort>,
+    identites: web::Data<dyn IdentitesExternesPort>,
+    services: ServicesConnexion,
+) -> Result<HttpResponse, MyError> {
+    let fournisseur = path.into_inner();
+    if let Some(erreur) = &retour.error {
+        let detail = retour.error_description.as_deref().unwrap_or(erreur);
+        return Err(MyError::Unauthorized(format!("Connexion refusée par le fournisseur: {}", detail)));
+    }
+    let (Some(code), Some(etat)) = (&retour.code, &retour.state) else {
+        return Err(MyError::BadRequest("Paramètres code et state attendus".to_string()));
+    };
+    // Un state obtenu dans un autre navigateur est refusé
+    if req.cookie(COOKIE_ETAT_OIDC).as_ref().map(Cookie::value) != Some(etat.as_str()) {
+        return Err(MyError::BadRequest("Demande de connexion lancée depuis un autre navigateur".to_string()));
+    }
+    let demande = identites
+        .consommer_demande(&hacher_jeton(etat))
+        .await?
+        .filter(|demande| demande.fournisseur == fournisseur)
+        .ok_or_else(|| MyError::BadRequest("Demande de connexion inconnue ou expirée".to_string()))?;
+
+    let profil = oidc.authentifier(&demande, code).await?;
+    let (utilisateur, entrees) =
+        compte_oidc(&fournisseur, &profil, identites.get_ref(), services.repo.get_ref(), &services.hacheur).await?;
+    for entree in entrees {
+        auditeur.consigner(entree).await;
+    }
+    let mut reponse = conclure_connexion(&req, &auditeur, &services, &utilisateur, &format!("oidc:{}", fournisseur)).await?;
+    let mut cookie = Cookie::named(COOKIE_ETAT_OIDC);
+    cookie.set_path(CHEMIN_COOKIE_ETAT_OIDC);
+    reponse
+        .add_removal_cookie(&cookie)
+        .map_err(|e| MyError::Custom(format!("Cookie non supprimé: {}", e)))?;
+    Ok(reponse)
+}
+
+// Jeton d'accès de courte durée au nom d'un client, pour reproduire un problème signalé au support
+pub async fn usurper(
+    req: HttpRequest,
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    path: web::Path<Uuid>,
+    repo: web::Data<dyn UtilisateurEntree>,
+    sessions: web::Data<dyn SessionPort>,
+    jwt: web::Data<ServiceJwt>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::UsurperIdentite)?;
+    if auth.claims.usurpateur.is_some() {
+        return Err(MyError::Forbidden("Usurpation déjà en cours".to_string()));
+    }
+    let cible = repo
+        .obtenir_par_id(path.into_inner())
+        .await?
+        .ok_or_else(|| MyError::NotFound("Utilisateur non trouvé".to_string()))?;
+    // Pas d'élévation de privilèges : seuls les comptes sans droits d'administration
+    if cible.id == auth.utilisateur.id || matches!(cible.role, Role::Admin | Role::Service) {
+        return Err(MyError::Forbidden("Ce compte ne peut pas être usurpé".to_string()));
+    }
+
+    // Session propre à l'usurpation : la déconnexion la révoque en base
+    let (appareil, adresse_ip) = origine(&req);
+    let session = Session::appareil(auth.utilisateur.id, jwt.duree_usurpation(), appareil, adresse_ip);
+    let session = sessions.creer_usurpation(&session, auth.claims.sid).await?;
+    let jeton = jwt.emettre_usurpation(&cible, auth.utilisateur.id, session.id)?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::DebutUsurpation, Some(auth.utilisateur.id), Some(cible.id))
+            .avec_changements(serde_json::json!({ "expire_dans": jeton.expire_dans })),
+    ).await;
+    Ok(HttpResponse::Created().json(jeton))
+}
+
+// Rotation : chaque jeton de rafraîchissement n'est échangeable qu'une fois
+pub async fn rafraichir(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    repo: web::Data<dyn UtilisateurEntree>,
+    sessions: web::Data<dyn SessionPort>,
+    config_session: web::Data<ConfigSession>,
+    jwt: web::Data<ServiceJwt>,
+    corps: web::Json<Rafraichissement>,
+) -> Result<HttpResponse, MyError> {
+    let session = match sessions.consommer_rafraichissement(&hacher_jeton(&corps.jeton_rafraichissement)).await? {
+        Rotation::Valide(session) => session,
+        Rotation::Reutilise(session) => {
+            // Un ancien jeton est rejoué : le légitime et le voleur ne sont plus distinguables
+            tracing::warn!("Réutilisation d'un jeton de rafraîchissement, session {} révoquée", session.id);
+            if let Err(e) = sessions.revoquer_par_id(session.id).await
+                && !matches!(e, MyError::NotFound(_))
+            {
+                return Err(e);
+            }
+            auditeur.consigner(
+                EntreeAudit::new(ActionAudit::ReutilisationRafraichissement, None, Some(session.id))
+                    .avec_changements(serde_json::json!({ "utilisateur_id": session.utilisateur_id })),
+            ).await;
+            return Err(MyError::Unauthorized("Jeton de rafraîchissement déjà utilisé".to_string()));
+        }
+        Rotation::Inconnu => return Err(MyError::Unauthorized("Jeton invalide ou expiré".to_string())),
+    };
+
+    // Le rôle est relu en base : un changement de droits est pris en compte au rafraîchissement
+    let utilisateur = match session.utilisateur_id {
+        Some(id) => repo.obtenir_par_id(id).await?,
+        None => None,
+    }
+    .ok_or_else(|| MyError::Unauthorized("Utilisateur inconnu".to_string()))?;
+
+    let (_, adresse_ip) = origine(&req);
+    let session = sessions
+        .toucher(session.id, adresse_ip, Utc::now() + config_session.duree_rafraichissement)
+        .await?;
+    let (clair, jeton) = JetonRafraichissement::new(session.id);
+    sessions.ajouter_rafraichissement(&jeton).await?;
+
+    Ok(HttpResponse::Ok().json(jwt.emettre(&utilisateur, session.id, clair)?))
+}
+
+// Ferme la session de l'appareil courant
+pub async fn deconnexion(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    sessions: web::Data<dyn SessionPort>,
+) -> Result<HttpResponse, MyError> {
+    // Fin d'usurpation : seule la session d'usurpation est révoquée, celle de l'administrateur reste ouverte
+    if auth.claims.usurpateur.is_some() {
+        if let Some(sid) = auth.claims.sid {
+            sessions.revoquer_par_id(sid).await?;
+        }
+        return Ok(HttpResponse::NoContent().finish());
+    }
+    if let Some(sid) = auth.claims.sid {
+        sessions.revoquer_par_id(sid).await?;
+        auditeur.consigner(EntreeAudit::new(ActionAudit::Deconnexion, Some(auth.utilisateur.id), Some(sid))).await;
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn lister_sessions(
+    auth: UtilisateurAuthentifie,
+    sessions: web::Data<dyn SessionPort>,
+) -> Result<HttpResponse, MyError> {
+    let actives: Vec<SessionActive> = sessions
+        .lister_actives(auth.utilisateur.id)
+        .await?
+        .iter()
+        .map(|session| SessionActive::depuis(session, auth.claims.sid))
+        .collect();
+    Ok(HttpResponse::Ok().json(actives))
+}
+
+pub async fn revoquer_session(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    sessions: web::Data<dyn SessionPort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::Sessions)?;
+    let id = id.into_inner();
+    // Une session d'un autre compte est traitée comme inexistante
+    match sessions.obtenir_par_id(id).await? {
+        Some(session) if session.utilisateur_id == Some(auth.utilisateur.id) => sessions.revoquer_par_id(id).await?,
+        _ => return Err(MyError::NotFound("Session non trouvée".to_string())),
+    }
+    auditeur.consigner(EntreeAudit::new(ActionAudit::RevocationSession, Some(auth.utilisateur.id), Some(id))).await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// « Se déconnecter partout », y compris sur l'appareil courant
+pub async fn revoquer_sessions(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    sessions: web::Data<dyn SessionPort>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::Sessions)?;
+    let revoquees = sessions.revoquer_tout(auth.utilisateur.id).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::RevocationSessions, Some(auth.utilisateur.id), Some(auth.utilisateur.id))
+            .avec_changements(serde_json::json!({ "sessions_revoquees": revoquees })),
+    ).await;
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "sessions_revoquees": revoquees })))
+}
+
+// Réponse identique que l'email existe ou non
+pub async fn mot_de_passe_oublie(
+    auditeur: Auditeur,
+    repo: web::Data<dyn UtilisateurEntree>,
+    jetons: web::Data<dyn JetonPort>,
+    mail: web::Data<dyn MailPort>,
+    config: web::Data<ConfigJetons>,
+    demande: web::Json<DemandeReinitialisation>,
+) -> Result<HttpResponse, MyError> {
+    if let Some(utilisateur) = repo.obtenir_par_email(&normaliser_email(&demande.email)).await? {
+        // Une nouvelle demande annule les liens encore valides
+        jetons.revoquer_tous(utilisateur.id, ObjetJeton::ReinitialisationMotDePasse).await?;
+        let (clair, jeton) = JetonUsageUnique::new(
+            utilisateur.id,
+            ObjetJeton::ReinitialisationMotDePasse,
+            config.duree_reinitialisation,
+        );
+        jetons.creer(&jeton).await?;
+        // Demande anonyme : pas d'acteur
+        auditeur.consigner(EntreeAudit::new(ActionAudit::DemandeReinitialisationMotDePasse, None, Some(utilisateur.id))).await;
+
+        let lien = format!("{}/reinitialiser-mot-de-passe?jeton={}", config.url_application, clair);
+        let courriel = Courriel::reinitialisation_mot_de_passe(&utilisateur.email, &lien, config.duree_reinitialisation);
+        // Envoi hors requête : le temps de réponse ne trahit pas l'existence du compte
+        let mail = mail.into_inner();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = mail.envoyer(&courriel).await {
+                tracing::error!("Mail de réinitialisation non envoyé: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "message": "Si un compte correspond à cette adresse, un lien de réinitialisation a été envoyé"
+    })))
+}
+
+pub async fn reinitialiser_mot_de_passe(
+    auditeur: Auditeur,
+    repo: web::Data<dyn UtilisateurEntree>,
+    jetons: web::Data<dyn JetonPort>,
+    sessions: web::Data<dyn SessionPort>,
+    hacheur: web::Data<HacheurMotDePasse>,
+    corps: web::Json<Reinitialisation>,
+) -> Result<HttpResponse, MyError> {
+    corps.validate()?;
+    let utilisateur_id = jetons
+        .consommer(ObjetJeton::ReinitialisationMotDePasse, &hacher_jeton(&corps.jeton))
+        .await?
+        .ok_or_else(|| MyError::BadRequest("Jeton invalide ou expiré".to_string()))?;
+
+    let avant = repo
+        .obtenir_par_id(utilisateur_id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Utilisateur non trouvé".to_string()))?;
+    let mut utilisateur = avant.clone();
+    utilisateur.mot_de_passe = hacheur.hacher(&corps.mot_de_passe).await?;
+    // Le lien reçu prouve la possession de l'adresse, comme la confirmation d'email ;
+    // l'entrée d'audit porte le changement de email_verifie
+    utilisateur.email_verifie = true;
+    utilisateur.date_update = Utc::now();
+    let utilisateur = repo.mettre_a_jour(&utilisateur, &Precondition::Toute).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::ReinitialisationMotDePasse, Some(utilisateur_id), Some(utilisateur_id))
+            .avec_changements(changements_utilisateur(Some(&avant), Some(&utilisateur))),
+    ).await;
+    jetons.revoquer_tous(utilisateur_id, ObjetJeton::ReinitialisationMotDePasse).await?;
+    // Les appareils connectés avec l'ancien mot de passe sont déconnectés
+    sessions.revoquer_tout(utilisateur_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn confirmer_email(
+    auditeur: Auditeur,
+    repo: web::Data<dyn UtilisateurEntree>,
+    jetons: web::Data<dyn JetonPort>,
+    corps: web::Json<ConfirmationEmail>,
+) -> Result<HttpResponse, MyError> {
+    let utilisateur_id = jetons
+        .consommer(ObjetJeton::VerificationEmail, &hacher_jeton(&corps.jeton))
+        .await?
+        .ok_or_else(|| MyError::BadRequest("Jeton invalide ou expiré".to_string()))?;
+
+    let mut utilisateur = repo
+        .obtenir_par_id(utilisateur_id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Utilisateur non trouvé".to_string()))?;
+    if !utilisateur.email_verifie {
+        let avant = utilisateur.clone();
+        utilisateur.email_verifie = true;
+        utilisateur.date_update = Utc::now();
+        let utilisateur = repo.mettre_a_jour(&utilisateur, &Precondition::Toute).await?;
+        auditeur.consigner(
+            EntreeAudit::new(ActionAudit::ConfirmationEmail, Some(utilisateur_id), Some(utilisateur_id))
+                .avec_changements(changements_utilisateur(Some(&avant), Some(&utilisateur))),
+        ).await;
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn renvoyer_verification(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    jetons: web::Data<dyn JetonPort>,
+    mail: web::Data<dyn MailPort>,
+    config: web::Data<ConfigJetons>,
+) -> Result<HttpResponse, MyError> {
+    if auth.utilisateur.email_verifie {
+        return Err(MyError::BadRequest("Adresse email déjà vérifiée".to_string()));
+    }
+    demander_verification_email(&auth.utilisateur, jetons.get_ref(), mail.into_inner(), &config).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::DemandeVerificationEmail, Some(auth.utilisateur.id), Some(auth.utilisateur.id)),
+    ).await;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+
+
+// Réponse identique que l'email existe ou non ; les envois vers une même adresse sont espacés
+pub async fn demander_lien_magique(
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    jetons: web::Data<dyn JetonPort>,
+    mail: web::Data<dyn MailPort>,
+    config: web::Data<ConfigJetons>,
+    demande: web::Json<DemandeLienMagique>,
+) -> Result<HttpResponse, MyError> {
+    let email = normaliser_email(&demande.email);
+    let cles = [cle_envoi(&email)];
+    verifier_tentatives(services.tentatives.get_ref(), &services.politique, &cles).await?;
+    enregistrer_echec(services.tentatives.get_ref(), &services.politique, &cles).await?;
+
+    if let Some(utilisateur) = services.repo.obtenir_par_email(&email).await?
+        && utilisateur.role != Role::Service
+    {
+        jetons.revoquer_tous(utilisateur.id, ObjetJeton::ConnexionLienMagique).await?;
+        let (clair, jeton) = JetonUsageUnique::new(utilisateur.id, ObjetJeton::ConnexionLienMagique, config.duree_lien_magique);
+        jetons.creer(&jeton).await?;
+        auditeur.consigner(EntreeAudit::new(ActionAudit::DemandeLienMagique, None, Some(utilisateur.id))).await;
+
+        let lien = format!("{}/connexion-magique?jeton={}", config.url_application, clair);
+        let courriel = Courriel::lien_magique(&utilisateur.email, &lien, config.duree_lien_magique);
+        let mail = mail.into_inner();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = mail.envoyer(&courriel).await {
+                tracing::error!("Mail de connexion non envoyé: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "message": "Si un compte correspond à cette adresse, un lien de connexion a été envoyé"
+    })))
+}
+
+pub async fn connexion_lien_magique(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    jetons: web::Data<dyn JetonPort>,
+    corps: web::Json<ConnexionLienMagique>,
+) -> Result<HttpResponse, MyError> {
+    let repo = services.repo.get_ref();
+    let utilisateur_id = jetons
+        .consommer(ObjetJeton::ConnexionLienMagique, &hacher_jeton(&corps.jeton))
+        .await?
+        .ok_or_else(|| MyError::BadRequest("Jeton invalide ou expiré".to_string()))?;
+    let mut utilisateur = repo
+        .obtenir_par_id(utilisateur_id)
+        .await?
+        .ok_or_else(|| MyError::Unauthorized("Jeton invalide ou expiré".to_string()))?;
+
+    // Le lien reçu prouve la possession de l'adresse
+    if !utilisateur.email_verifie {
+        let avant = utilisateur.clone();
+        utilisateur.email_verifie = true;
+        utilisateur.date_update = Utc::now();
+        utilisateur = repo.mettre_a_jour(&utilisateur, &Precondition::Toute).await?;
+        auditeur.consigner(
+            EntreeAudit::new(ActionAudit::ConfirmationEmail, Some(utilisateur_id), Some(utilisateur_id))
+                .avec_changements(changements_utilisateur(Some(&avant), Some(&utilisateur))),
+        ).await;
+    }
+
+    conclure_connexion(&req, &auditeur, &services, &utilisateur, "lien_magique").await
+}
+
+// Nouveau numéro en attente : la connexion par SMS n'est ouverte qu'une fois le code confirmé
+pub async fn changer_telephone(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    telephones: web::Data<dyn TelephonePort>,
+    sms: web::Data<dyn SmsPort>,
+    config: web::Data<ConfigJetons>,
+    corps: web::Json<ChangementTelephone>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::Telephone)?;
+    let numero = normaliser_telephone(&corps.telephone)?;
+    let cles = [cle_envoi(&numero)];
+    verifier_tentatives(services.tentatives.get_ref(), &services.politique, &cles).await?;
+    enregistrer_echec(services.tentatives.get_ref(), &services.politique, &cles).await?;
+
+    let utilisateur_id = auth.utilisateur.id;
+    let avant = telephones.obtenir(utilisateur_id).await?;
+    // Même numéro, déjà vérifié : rien à faire
+    if let Some(avant) = &avant
+        && avant.numero == numero
+        && avant.date_verification.is_some()
+    {
+        return Ok(HttpResponse::Ok().json(avant));
+    }
+    let telephone = telephones.enregistrer(utilisateur_id, &numero).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::ChangementTelephone, Some(utilisateur_id), Some(utilisateur_id))
+            .avec_changements(serde_json::json!({
+                "telephone": { "avant": avant.map(|_| MASQUE), "apres": MASQUE },
+            })),
+    ).await;
+
+    let (code, stocke) = CodeSms::new(utilisateur_id, ObjetCodeSms::VerificationTelephone, &numero, config.duree_code_sms);
+    telephones.creer_code(&stocke).await?;
+    sms.envoyer(&Sms::verification_telephone(&numero, &code, config.duree_code_sms)).await?;
+
+    Ok(HttpResponse::Accepted().json(telephone))
+}
+
+pub async fn confirmer_telephone(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    telephones: web::Data<dyn TelephonePort>,
+    tentatives: web::Data<dyn TentativesPort>,
+    politique: web::Data<PolitiqueTentatives>,
+    corps: web::Json<ConfirmationTelephone>,
+) -> Result<HttpResponse, MyError> {
+    auth.interdire_en_usurpation(ActionSensible::Telephone)?;
+    let utilisateur_id = auth.utilisateur.id;
+    // Compteur propre : un code mal saisi ne doit pas verrouiller la connexion au compte
+    let cles = [cle_verification_telephone(utilisateur_id)];
+    verifier_tentatives(tentatives.get_ref(), &politique, &cles).await?;
+
+    let telephone = telephones
+        .obtenir(utilisateur_id)
+        .await?
+        .filter(|telephone| telephone.date_verification.is_none())
+        .ok_or_else(|| MyError::BadRequest("Aucun numéro en attente de vérification".to_string()))?;
+    let code_hache = hacher_jeton(corps.code.trim());
+    if !telephones
+        .consommer_code(utilisateur_id, ObjetCodeSms::VerificationTelephone, &telephone.numero, &code_hache)
+        .await?
+    {
+        enregistrer_echec(tentatives.get_ref(), &politique, &cles).await?;
+        return Err(MyError::BadRequest("Code invalide ou expiré".to_string()));
+    }
+
+    let telephone = telephones.marquer_verifie(utilisateur_id, &telephone.numero).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::VerificationTelephone, Some(utilisateur_id), Some(utilisateur_id))
+            .avec_changements(serde_json::json!({ "telephone": MASQUE })),
+    ).await;
+    Ok(HttpResponse::Ok().json(telephone))
+}
+
+// Réponse identique que le numéro soit connu ou non ; les envois vers un même numéro sont espacés
+pub async fn demander_code_sms(
+    telephones: web::Data<dyn TelephonePort>,
+    sms: web::Data<dyn SmsPort>,
+    tentatives: web::Data<dyn TentativesPort>,
+    politique: web::Data<PolitiqueTentatives>,
+    config: web::Data<ConfigJetons>,
+    demande: web::Json<DemandeCodeSms>,
+) -> Result<HttpResponse, MyError> {
+    let numero = normaliser_telephone(&demande.telephone)?;
+    let cles = [cle_envoi(&numero)];
+    verifier_tentatives(tentatives.get_ref(), &politique, &cles).await?;
+    enregistrer_echec(tentatives.get_ref(), &politique, &cles).await?;
+
+    if let Some(telephone) = telephones.obtenir_verifie(&numero).await? {
+        let (code, stocke) = CodeSms::new(telephone.utilisateur_id, ObjetCodeSms::Connexion, &numero, config.duree_code_sms);
+        telephones.creer_code(&stocke).await?;
+        // Envoi hors requête, comme pour les mails
+        let message = Sms::code_connexion(&numero, &code, config.duree_code_sms);
+        let sms = sms.into_inner();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = sms.envoyer(&message).await {
+                tracing::error!("SMS de connexion non envoyé: {}", e);
+            }
+        });
+    }
+
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "message": "Si un compte correspond à ce numéro, un code de connexion a été envoyé"
+    })))
+}
+
+pub async fn connexion_sms(
+    req: HttpRequest,
+    auditeur: Auditeur,
+    services: ServicesConnexion,
+    telephones: web::Data<dyn TelephonePort>,
+    corps: web::Json<ConnexionSms>,
+) -> Result<HttpResponse, MyError> {
+    let numero = normaliser_telephone(&corps.telephone)?;
+    let utilisateur = match telephones.obtenir_verifie(&numero).await? {
+        Some(telephone) => services.repo.obtenir_par_id(telephone.utilisateur_id).await?,
+        None => None,
+    };
+    // Numéro inconnu : seuls les échecs de l'adresse IP sont comptés
+    let cles = match &utilisateur {
+        Some(utilisateur) => cles_tentative(&req, &services.regles_email, &utilisateur.email),
+        None => req.peer_addr().map(|adresse| cle_ip(adresse.ip())).into_iter().collect(),
+    };
+    let tentatives = services.tentatives.get_ref();
+    verifier_tentatives(tentatives, &services.politique, &cles).await?;
+
+    let code_hache = hacher_jeton(corps.code.trim());
+    let utilisateur = match utilisateur {
+        Some(utilisateur)
+            if telephones
+                .consommer_code(utilisateur.id, ObjetCodeSms::Connexion, &numero, &code_hache)
+                .await? =>
+        {
+            utilisateur
+        }
+        _ => {
+            enregistrer_echec(tentatives, &services.politique, &cles).await?;
+            return Err(MyError::Unauthorized("Code invalide ou expiré".to_string()));
+        }
+    };
+
+    conclure_connexion(&req, &auditeur, &services, &utilisateur, "sms").await
+}
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/auth")
+            .route("/login", web::post().to(connexion))
+            .route("/refresh", web::post().to(rafraichir))
+            .route("/logout", web::post().to(deconnexion))
+            .route("/sessions", web::get().to(lister_sessions))
+            .route("/sessions", web::delete().to(revoquer_sessions))
+            .route("/sessions/{id}", web::delete().to(revoquer_session))
+            .route("/password/forgot", web::post().to(mot_de_passe_oublie))
+            .route("/password/reset", web::post().to(reinitialiser_mot_de_passe))
+            .route("/email/confirm", web::post().to(confirmer_email))
+            .route("/email/resend", web::post().to(renvoyer_verification))
+            .route("/2fa/verify", web::post().to(verifier_deux_facteurs))
+            .route("/2fa/enroll", web::post().to(inscrire_deux_facteurs))
+            .route("/2fa/confirm", web::post().to(confirmer_deux_facteurs))
+            .route("/2fa", web::delete().to(desactiver_deux_facteurs))
+            .route("/impersonate/{id}", web::post().to(usurper))
+            .route("/oidc/{fournisseur}/authorize", web::get().to(autoriser_oidc))
+            .route("/oidc/{fournisseur}/callback", web::get().to(retour_oidc))
+            .route("/magic-link", web::post().to(demander_lien_magique))
+            .route("/magic-link/verify", web::post().to(connexion_lien_magique))
+            .route("/sms", web::post().to(demander_code_sms))
+            .route("/sms/verify", web::post().to(connexion_sms))
+            .route("/phone", web::put().to(changer_telephone))
+            .route("/phone/confirm", web::post().to(confirmer_telephone))
+    );
+}
@@ -0,0 +1,140 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::ports::categories::CategoriePort;
+use crate::domain::audit::{changements, ActionAudit, EntreeAudit};
+use crate::domain::categorie::{CreationCategorie, DeplacementCategorie, MiseAJourCategorie, NoeudCategorie};
+use crate::domain::models::Categorie;
+use crate::domain::error::MyError;
+use crate::domain::role::Permission;
+use crate::adaptateurs::entrer::auth::UtilisateurAuthentifie;
+use crate::adaptateurs::entrer::audit::Auditeur;
+
+
+// Arborescence complète, publique
+pub async fn lister(repo: web::Data<dyn CategoriePort>) -> Result<HttpResponse, MyError> {
+    Ok(HttpResponse::Ok().json(NoeudCategorie::arbre(repo.lister().await?)))
+}
+
+pub async fn obtenir(
+    repo: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    let categorie = repo
+        .obtenir(id.into_inner())
+        .await?
+        .ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))?;
+    Ok(HttpResponse::Ok().json(categorie))
+}
+
+pub async fn fil_ariane(
+    repo: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    let fil = repo.fil_ariane(id.into_inner()).await?;
+    if fil.is_empty() {
+        return Err(MyError::NotFound("Catégorie non trouvée".to_string()));
+    }
+    Ok(HttpResponse::Ok().json(fil))
+}
+
+pub async fn creer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn CategoriePort>,
+    creation: web::Json<CreationCategorie>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let creation = creation.into_inner();
+    creation.validate()?;
+
+    let categorie = repo.creer(&Categorie::new(creation)).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::CreationCategorie, Some(auth.utilisateur.id), Some(categorie.id))
+            .avec_changements(changements(None, Some(&categorie))),
+    ).await;
+    Ok(HttpResponse::Created().json(categorie))
+}
+
+pub async fn mettre_a_jour(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+    mise_a_jour: web::Json<MiseAJourCategorie>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let mise_a_jour = mise_a_jour.into_inner();
+    mise_a_jour.validate()?;
+
+    let avant = repo
+        .obtenir(id.into_inner())
+        .await?
+        .ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))?;
+    let mut categorie = avant.clone();
+    categorie.remplacer(mise_a_jour);
+    let categorie = repo.mettre_a_jour(&categorie).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::ModificationCategorie, Some(auth.utilisateur.id), Some(categorie.id))
+            .avec_changements(changements(Some(&avant), Some(&categorie))),
+    ).await;
+    Ok(HttpResponse::Ok().json(categorie))
+}
+
+// Le sous-arbre suit la catégorie déplacée
+pub async fn deplacer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+    deplacement: web::Json<DeplacementCategorie>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let avant = repo
+        .obtenir(id.into_inner())
+        .await?
+        .ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))?;
+    let categorie = repo.deplacer(avant.id, deplacement.parent_id).await?;
+    if categorie.parent_id != avant.parent_id {
+        auditeur.consigner(
+            EntreeAudit::new(ActionAudit::DeplacementCategorie, Some(auth.utilisateur.id), Some(categorie.id))
+                .avec_changements(changements(Some(&avant), Some(&categorie))),
+        ).await;
+    }
+    Ok(HttpResponse::Ok().json(categorie))
+}
+
+pub async fn supprimer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let id = id.into_inner();
+    let avant = repo
+        .obtenir(id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))?;
+    repo.supprimer(id).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::SuppressionCategorie, Some(auth.utilisateur.id), Some(id))
+            .avec_changements(changements(Some(&avant), None)),
+    ).await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/categories")
+            .route("", web::get().to(lister))
+            .route("", web::post().to(creer))
+            .route("/{id}", web::get().to(obtenir))
+            .route("/{id}", web::put().to(mettre_a_jour))
+            .route("/{id}", web::delete().to(supprimer))
+            .route("/{id}/parent", web::put().to(deplacer))
+            .route("/{id}/fil-ariane", web::get().to(fil_ariane))
+    );
+}
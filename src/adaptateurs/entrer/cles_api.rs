@@ -0,0 +1,95 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::ports::cles_api::ClesApiPort;
+use crate::ports::users::UtilisateurEntree;
+use crate::domain::audit::{ActionAudit, EntreeAudit};
+use crate::domain::cle_api::{CleApi, CleApiCreee, CreationCleApi, RequeteClesApi};
+use crate::domain::error::MyError;
+use crate::domain::role::{Permission, Role};
+use crate::adaptateurs::entrer::auth::UtilisateurAuthentifie;
+use crate::adaptateurs::entrer::audit::Auditeur;
+
+
+// La clé en clair n'est renvoyée qu'ici ; seul son hash est conservé
+pub async fn creer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn UtilisateurEntree>,
+    cles: web::Data<dyn ClesApiPort>,
+    creation: web::Json<CreationCleApi>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererClesApi)?;
+    let creation = creation.into_inner();
+    creation.validate()?;
+
+    let compte = repo
+        .obtenir_par_id(creation.utilisateur_id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Utilisateur non trouvé".to_string()))?;
+    if compte.role != Role::Service {
+        return Err(MyError::BadRequest("Les clés d'API sont réservées aux comptes de service".to_string()));
+    }
+    // Une portée hors du rôle ne donnerait aucun droit : elle est refusée plutôt qu'ignorée
+    if let Some(portee) = creation.portees.iter().find(|portee| !compte.role.a_permission(**portee)) {
+        return Err(MyError::BadRequest(format!("Portée non autorisée pour un compte de service: {:?}", portee)));
+    }
+
+    let (clair, cle) = CleApi::new(creation);
+    let cle = cles.creer(&cle).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::CreationCleApi, Some(auth.utilisateur.id), Some(cle.id))
+            .avec_changements(json!({
+                "utilisateur_id": cle.utilisateur_id,
+                "prefixe": cle.prefixe,
+                "portees": cle.portees,
+            })),
+    ).await;
+
+    Ok(HttpResponse::Created().json(CleApiCreee { cle: clair, details: cle }))
+}
+
+pub async fn lister(
+    auth: UtilisateurAuthentifie,
+    cles: web::Data<dyn ClesApiPort>,
+    requete: web::Query<RequeteClesApi>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererClesApi)?;
+    Ok(HttpResponse::Ok().json(cles.lister(requete.utilisateur_id).await?))
+}
+
+pub async fn revoquer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    cles: web::Data<dyn ClesApiPort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererClesApi)?;
+    let id = id.into_inner();
+    let deja_revoquee = cles
+        .obtenir(id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Clé d'API non trouvée".to_string()))?
+        .date_revocation
+        .is_some();
+    let cle = cles.revoquer(id).await?;
+    if !deja_revoquee {
+        auditeur.consigner(
+            EntreeAudit::new(ActionAudit::RevocationCleApi, Some(auth.utilisateur.id), Some(cle.id))
+                .avec_changements(json!({ "utilisateur_id": cle.utilisateur_id, "prefixe": cle.prefixe })),
+        ).await;
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/cles-api")
+            .route("", web::post().to(creer))
+            .route("", web::get().to(lister))
+            .route("/{id}", web::delete().to(revoquer))
+    );
+}
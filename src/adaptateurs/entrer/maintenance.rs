@@ -0,0 +1,47 @@
+use crate::ports::users::UtilisateurEntree;
+use crate::domain::email::{normaliser_email, RapportNormalisation, ReglesEmail};
+use crate::domain::error::MyError;
+
+// Commandes de maintenance : lancées en ligne de commande, le serveur HTTP ne démarre pas
+
+// Sans --appliquer, n'écrit rien et se contente du rapport
+pub async fn normaliser_emails(repo: &dyn UtilisateurEntree, regles: &ReglesEmail, appliquer: bool) -> Result<(), MyError> {
+    let utilisateurs = repo.lister_tous().await?;
+    let rapport = RapportNormalisation::depuis(&utilisateurs, regles);
+
+    println!("{} compte(s), {} email(s) à normaliser", utilisateurs.len(), rapport.a_normaliser.len());
+    for (id, actuel, normalise) in &rapport.a_normaliser {
+        let statut = if rapport.est_en_doublon(actuel, regles) { " (doublon, ignoré)" } else { "" };
+        println!("  {} : {} -> {}{}", id, actuel, normalise, statut);
+    }
+
+    println!("{} groupe(s) de doublons", rapport.doublons.len());
+    for (cle, comptes) in &rapport.doublons {
+        println!("  {}", cle);
+        for compte in comptes {
+            println!("    {} {} (créé le {})", compte.id, compte.email, compte.date_creation);
+        }
+    }
+
+    if !appliquer {
+        println!("Aucune modification (relancer avec --appliquer)");
+        return Ok(());
+    }
+
+    let mut normalises = 0;
+    // Tous les comptes, pour recalculer aussi la clé après un changement de règles ;
+    // les comptes supprimés sont réécrits aussi : ils comptent pour l'index unique
+    for utilisateur in &utilisateurs {
+        if rapport.est_en_doublon(&utilisateur.email, regles) {
+            continue;
+        }
+        if repo.reecrire_email(utilisateur.id, &normaliser_email(&utilisateur.email)).await? {
+            normalises += 1;
+        }
+    }
+    println!("{} compte(s) mis à jour", normalises);
+    if !rapport.doublons.is_empty() {
+        println!("Les doublons gardent leur ancienne clé et doivent être fusionnés à la main");
+    }
+    Ok(())
+}
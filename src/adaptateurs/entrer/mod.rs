@@ -1,2 +1,8 @@
 pub mod users;
-pub  mod auth;
\ No newline at end of file
+pub  mod auth;
+pub mod sessions;
+pub mod maintenance;
+pub mod audit;
+pub mod cles_api;
+pub mod produits;
+pub mod categories;
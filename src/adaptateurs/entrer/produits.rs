@@ -0,0 +1,140 @@
+use actix_web::{web, HttpResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::ports::produits::ProduitPort;
+use crate::ports::categories::CategoriePort;
+use crate::domain::audit::{changements, ActionAudit, EntreeAudit};
+use crate::domain::models::Produit;
+use crate::domain::produit::{CreationProduit, MiseAJourProduit, RequeteProduits};
+use crate::domain::error::MyError;
+use crate::domain::role::Permission;
+use crate::adaptateurs::entrer::auth::UtilisateurAuthentifie;
+use crate::adaptateurs::entrer::audit::Auditeur;
+
+
+// Anonymes et clients ne voient que les produits publiés
+fn gere_catalogue(auth: &Option<UtilisateurAuthentifie>) -> bool {
+    auth.as_ref().is_some_and(|auth| auth.exiger(Permission::GererCatalogue).is_ok())
+}
+
+pub async fn lister(
+    auth: Option<UtilisateurAuthentifie>,
+    repo: web::Data<dyn ProduitPort>,
+    requete: web::Query<RequeteProduits>,
+) -> Result<HttpResponse, MyError> {
+    let mut requete = requete.into_inner();
+    if !gere_catalogue(&auth) {
+        requete.publie = Some(true);
+    }
+    Ok(HttpResponse::Ok().json(repo.lister(&requete).await?))
+}
+
+pub async fn obtenir(
+    auth: Option<UtilisateurAuthentifie>,
+    repo: web::Data<dyn ProduitPort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    // Un produit non publié est introuvable pour qui ne gère pas le catalogue
+    let produit = repo
+        .obtenir(id.into_inner())
+        .await?
+        .filter(|produit| produit.est_publie || gere_catalogue(&auth))
+        .ok_or_else(|| MyError::NotFound("Produit non trouvé".to_string()))?;
+    Ok(HttpResponse::Ok().json(produit))
+}
+
+// Catégories de la racine jusqu'à celle du produit ; vide s'il n'en a pas
+pub async fn fil_ariane(
+    auth: Option<UtilisateurAuthentifie>,
+    repo: web::Data<dyn ProduitPort>,
+    categories: web::Data<dyn CategoriePort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    let produit = repo
+        .obtenir(id.into_inner())
+        .await?
+        .filter(|produit| produit.est_publie || gere_catalogue(&auth))
+        .ok_or_else(|| MyError::NotFound("Produit non trouvé".to_string()))?;
+    let fil = match produit.categorie_id {
+        Some(categorie_id) => categories.fil_ariane(categorie_id).await?,
+        None => Vec::new(),
+    };
+    Ok(HttpResponse::Ok().json(fil))
+}
+
+pub async fn creer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn ProduitPort>,
+    creation: web::Json<CreationProduit>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let creation = creation.into_inner().normalisee();
+    creation.validate()?;
+
+    let produit = repo.creer(&Produit::new(creation)).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::CreationProduit, Some(auth.utilisateur.id), Some(produit.id))
+            .avec_changements(changements(None, Some(&produit))),
+    ).await;
+    Ok(HttpResponse::Created().json(produit))
+}
+
+pub async fn mettre_a_jour(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn ProduitPort>,
+    id: web::Path<Uuid>,
+    mise_a_jour: web::Json<MiseAJourProduit>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let mise_a_jour = mise_a_jour.into_inner().normalisee();
+    mise_a_jour.validate()?;
+
+    let avant = repo
+        .obtenir(id.into_inner())
+        .await?
+        .ok_or_else(|| MyError::NotFound("Produit non trouvé".to_string()))?;
+    let mut produit = avant.clone();
+    produit.remplacer(mise_a_jour);
+    let produit = repo.mettre_a_jour(&produit).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::ModificationProduit, Some(auth.utilisateur.id), Some(produit.id))
+            .avec_changements(changements(Some(&avant), Some(&produit))),
+    ).await;
+    Ok(HttpResponse::Ok().json(produit))
+}
+
+pub async fn supprimer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    repo: web::Data<dyn ProduitPort>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, MyError> {
+    auth.exiger(Permission::GererCatalogue)?;
+    let id = id.into_inner();
+    let avant = repo
+        .obtenir(id)
+        .await?
+        .ok_or_else(|| MyError::NotFound("Produit non trouvé".to_string()))?;
+    repo.supprimer(id).await?;
+    auditeur.consigner(
+        EntreeAudit::new(ActionAudit::SuppressionProduit, Some(auth.utilisateur.id), Some(id))
+            .avec_changements(changements(Some(&avant), None)),
+    ).await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/produits")
+            .route("", web::get().to(lister))
+            .route("", web::post().to(creer))
+            .route("/{id}", web::get().to(obtenir))
+            .route("/{id}", web::put().to(mettre_a_jour))
+            .route("/{id}", web::delete().to(supprimer))
+            .route("/{id}/fil-ariane", web::get().to(fil_ariane))
+    );
+}
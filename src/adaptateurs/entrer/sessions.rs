@@ -0,0 +1,86 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest, HttpResponse};
+use chrono::Utc;
+use futures_util::future::LocalBoxFuture;
+
+use crate::ports::sessions::SessionPort;
+use crate::domain::models::Session;
+use crate::domain::session::ConfigSession;
+use crate::domain::error::MyError;
+use crate::adaptateurs::entrer::auth::UtilisateurAuthentifie;
+
+pub const EN_TETE_SESSION: &str = "X-Session-Token";
+
+
+pub fn token_de_session(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(EN_TETE_SESSION)
+        .and_then(|valeur| valeur.to_str().ok())
+        .map(str::to_string)
+}
+
+// Extracteur : session valide désignée par l'en-tête X-Session-Token
+pub struct SessionCourante(pub Session);
+
+impl FromRequest for SessionCourante {
+    type Error = MyError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let req = req.clone();
+        Box::pin(async move {
+            let sessions = req
+                .app_data::<web::Data<dyn SessionPort>>()
+                .ok_or_else(|| MyError::Custom("SessionPort non configuré".to_string()))?;
+            let token = token_de_session(&req)
+                .ok_or_else(|| MyError::Unauthorized("Session manquante".to_string()))?;
+            let session = sessions
+                .obtenir_par_token(&token)
+                .await?
+                .ok_or_else(|| MyError::Unauthorized("Session inconnue ou expirée".to_string()))?;
+            Ok(SessionCourante(session))
+        })
+    }
+}
+
+// Ouvre une session invitée, ou rattachée au compte si l'appelant est authentifié
+pub async fn creer(
+    sessions: web::Data<dyn SessionPort>,
+    config: web::Data<ConfigSession>,
+    auth: Option<UtilisateurAuthentifie>,
+) -> Result<HttpResponse, MyError> {
+    let session = Session::new(auth.map(|auth| auth.utilisateur.id), config.duree);
+    Ok(HttpResponse::Created().json(sessions.creer(&session).await?))
+}
+
+pub async fn obtenir(session: SessionCourante) -> Result<HttpResponse, MyError> {
+    Ok(HttpResponse::Ok().json(session.0))
+}
+
+pub async fn prolonger(
+    session: SessionCourante,
+    sessions: web::Data<dyn SessionPort>,
+    config: web::Data<ConfigSession>,
+) -> Result<HttpResponse, MyError> {
+    let session = sessions.prolonger(&session.0.token, Utc::now() + config.duree).await?;
+    Ok(HttpResponse::Ok().json(session))
+}
+
+pub async fn revoquer(
+    session: SessionCourante,
+    sessions: web::Data<dyn SessionPort>,
+) -> Result<HttpResponse, MyError> {
+    sessions.revoquer(&session.0.token).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+
+
+pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/session")
+            .route("", web::post().to(creer))
+            .route("", web::get().to(obtenir))
+            .route("/prolonger", web::post().to(prolonger))
+            .route("", web::delete().to(revoquer))
+    );
+}
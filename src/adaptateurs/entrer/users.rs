@@ -1,95 +1,320 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
 use actix_web::ResponseError;
+use futures_util::future::{ready, Ready};
 
 use uuid::Uuid;
+use validator::Validate;
 use chrono::Utc;
 use crate::ports::users::UtilisateurEntree;
-use crate::domain::user::{Utilisateur, CreateUser, UpdateUser};
+use crate::ports::jetons::JetonPort;
+use crate::ports::mail::MailPort;
+use crate::ports::tentatives::TentativesPort;
+use crate::ports::donnees_personnelles::DonneesPersonnellesPort;
+use crate::ports::sessions::SessionPort;
+use crate::domain::user::{
+    Correctif, CreateUser, ProfilPersonnel, RequeteUtilisateurs, UpdateUser, Utilisateur, VueUtilisateur,
+};
 use crate::domain::error::MyError;
+use crate::domain::mot_de_passe::HacheurMotDePasse;
+use crate::domain::role::{ChangerRole, Permission};
+use crate::domain::jeton::ConfigJetons;
+use crate::domain::tentatives::cle_compte;
+use crate::domain::concurrence::Precondition;
+use crate::domain::email::{normaliser_email, ReglesEmail};
+use crate::domain::donnees_personnelles::{Anonymisation, ExportDonnees};
+use crate::domain::audit::{changements_utilisateur, ActionAudit, EntreeAudit};
+use crate::domain::usurpation::ActionSensible;
+use crate::adaptateurs::entrer::auth::{demander_verification_email, donnee_app, UtilisateurAuthentifie};
+use crate::adaptateurs::entrer::audit::Auditeur;
 
 
+// Extracteur : version attendue par l'appelant (If-Match), obligatoire sur les écritures
+pub struct VersionAttendue(pub Precondition);
+
+impl FromRequest for VersionAttendue {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let valeur = req.headers().get(header::IF_MATCH).and_then(|valeur| valeur.to_str().ok());
+        ready(Precondition::depuis_if_match(valeur).map(VersionAttendue))
+    }
+}
+
+// Extracteur : dépendances de la création et de la modification d'un compte
+pub struct ServicesCompte {
+    pub repo: web::Data<dyn UtilisateurEntree>,
+    pub hacheur: web::Data<HacheurMotDePasse>,
+    pub jetons: web::Data<dyn JetonPort>,
+    pub mail: web::Data<dyn MailPort>,
+    pub config: web::Data<ConfigJetons>,
+}
+
+impl FromRequest for ServicesCompte {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let services = || -> Result<Self, MyError> {
+            Ok(ServicesCompte {
+                repo: donnee_app(req, "Repository")?,
+                hacheur: donnee_app(req, "HacheurMotDePasse")?,
+                jetons: donnee_app(req, "JetonPort")?,
+                mail: donnee_app(req, "MailPort")?,
+                config: donnee_app(req, "ConfigJetons")?,
+            })
+        };
+        ready(services())
+    }
+}
+
+impl ServicesCompte {
+    // Le compte reste valide même si l'envoi échoue : le lien peut être redemandé
+    async fn demander_verification(&self, user: &Utilisateur) {
+        let mail = self.mail.clone().into_inner();
+        if let Err(e) = demander_verification_email(user, self.jetons.get_ref(), mail, &self.config).await {
+            tracing::error!("Vérification email non demandée pour {}: {}", user.id, e);
+        }
+    }
+}
+
+// Réponse portant l'ETag de la version renvoyée
+fn avec_etag(mut reponse: HttpResponseBuilder, user: &Utilisateur) -> HttpResponseBuilder {
+    reponse.insert_header((header::ETAG, user.etag()));
+    reponse
+}
 
 
 pub async fn obtenir_par_id(
+    auth: UtilisateurAuthentifie,
     path: web::Path<Uuid>,
     repo: web::Data<dyn UtilisateurEntree>,
 ) -> impl Responder {
-    match repo.obtenir_par_id(path.into_inner()).await {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::LireUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match repo.obtenir_par_id(id).await {
+        Ok(Some(user)) => avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user)),
         Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
 
 pub async fn creer(
-    repo: web::Data<dyn UtilisateurEntree>,
+    auditeur: Auditeur,
+    services: ServicesCompte,
     user: web::Json<CreateUser>,
 ) -> impl Responder {
-    let create_user = user.into_inner();
-    let new_user = Utilisateur::new(create_user);
-    match repo.creer(&new_user).await {
-        Ok(user) => HttpResponse::Created().json(user),
+    let create_user = user.into_inner().normalisee();
+    if let Err(e) = create_user.validate() {
+        let e = MyError::from(e);
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    let new_user = match Utilisateur::new(create_user, &services.hacheur).await {
+        Ok(new_user) => new_user,
+        Err(e) => return HttpResponse::build(e.status_code()).json(e),
+    };
+    match services.repo.creer(&new_user).await {
+        Ok(user) => {
+            auditeur.consigner(
+                EntreeAudit::new(ActionAudit::CreationUtilisateur, Some(user.id), Some(user.id))
+                    .avec_changements(changements_utilisateur(None, Some(&user))),
+            ).await;
+            services.demander_verification(&user).await;
+            avec_etag(HttpResponse::Created(), &user).json(ProfilPersonnel::from(&user))
+        }
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
 
 pub async fn obtenir_par_nom(
+    auth: UtilisateurAuthentifie,
     path: web::Path<String>,
     repo: web::Data<dyn UtilisateurEntree>,
 ) -> impl Responder {
+    if let Err(e) = auth.exiger(Permission::LireUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
     match repo.obtenir_par_nom(&path.into_inner()).await {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
+        Ok(Some(user)) => avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user)),
         Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
 
 pub async fn obtenir_par_email(
+    auth: UtilisateurAuthentifie,
     path: web::Path<String>,
     repo: web::Data<dyn UtilisateurEntree>,
 ) -> impl Responder {
-    match repo.obtenir_par_email(&path.into_inner()).await {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
+    if let Err(e) = auth.exiger(Permission::LireUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match repo.obtenir_par_email(&normaliser_email(&path.into_inner())).await {
+        Ok(Some(user)) => avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user)),
         Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
 
-pub async fn obtenir_tous(
+pub async fn lister(
+    auth: UtilisateurAuthentifie,
     repo: web::Data<dyn UtilisateurEntree>,
+    requete: web::Query<RequeteUtilisateurs>,
 ) -> impl Responder {
-    match repo.obtenir_tous().await {
-        Ok(users) => HttpResponse::Ok().json(users),
+    if let Err(e) = auth.exiger(Permission::ListerUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    if requete.inclure_supprimes
+        && let Err(e) = auth.exiger(Permission::GererSuppressions)
+    {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match repo.lister(&requete).await {
+        Ok(page) => HttpResponse::Ok().json(
+            page.map(|user| VueUtilisateur::pour(&auth.utilisateur, &user))
+        ),
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
 
+// Applique un remplacement complet des champs modifiables, puis l'écrit sous condition de version
+async fn remplacer(
+    auth: &UtilisateurAuthentifie,
+    mut existing_user: Utilisateur,
+    modification: UpdateUser,
+    version: &Precondition,
+    services: &ServicesCompte,
+) -> Result<Utilisateur, MyError> {
+    let modification = modification.normalisee();
+    modification.validate()?;
+    // Refus immédiat d'une version périmée, avant tout hachage
+    version.verifier(existing_user.date_update)?;
+
+    let email_modifie = modification.email != existing_user.email;
+    if email_modifie {
+        auth.interdire_en_usurpation(ActionSensible::Email)?;
+        // La nouvelle adresse doit être confirmée à son tour
+        existing_user.email_verifie = false;
+    }
+    existing_user.email = modification.email;
+    if let Some(mot_de_passe) = &modification.mot_de_passe {
+        auth.interdire_en_usurpation(ActionSensible::MotDePasse)?;
+        existing_user.mot_de_passe = services.hacheur.hacher(mot_de_passe).await?;
+    }
+    existing_user.prenom = modification.prenom;
+    existing_user.nom = modification.nom;
+    existing_user.date_update = Utc::now();
+    // Le rôle n'est pas modifié ici, voir changer_role
+
+    // L'écriture reste conditionnelle : une modification concurrente peut survenir entre-temps
+    let user = services.repo.mettre_a_jour(&existing_user, version).await?;
+    if email_modifie {
+        services.demander_verification(&user).await;
+    }
+    Ok(user)
+}
+
+// PUT : remplacement complet (email, prénom et nom obligatoires)
 pub async fn mettre_a_jour(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    version: VersionAttendue,
     path: web::Path<Uuid>,
-    repo: web::Data<dyn UtilisateurEntree>,
+    services: ServicesCompte,
     update_user: web::Json<UpdateUser>,
 ) -> impl Responder {
     let id = path.into_inner();
-    match repo.obtenir_par_id(id).await {
-        Ok(Some(mut existing_user)) => {
-            // Mettre à jour les champs non nuls
-            if let Some(email) = &update_user.email {
-                existing_user.email = email.clone();
-            }
-            if let Some(mot_de_passe) = &update_user.mot_de_passe {
-                existing_user.mot_de_passe = mot_de_passe.clone();
-            }
-            if let Some(prenom) = &update_user.prenom {
-                existing_user.prenom = prenom.clone();
-            }
-            if let Some(nom) = &update_user.nom {
-                existing_user.nom = nom.clone();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::ModifierUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match services.repo.obtenir_par_id(id).await {
+        Ok(Some(existing_user)) => {
+            let avant = existing_user.clone();
+            let resultat = remplacer(
+                &auth,
+                existing_user,
+                update_user.into_inner(),
+                &version.0,
+                &services,
+            )
+            .await;
+            match resultat {
+                Ok(user) => {
+                    auditeur.consigner(
+                        EntreeAudit::new(ActionAudit::ModificationUtilisateur, Some(auth.utilisateur.id), Some(user.id))
+                            .avec_changements(changements_utilisateur(Some(&avant), Some(&user))),
+                    ).await;
+                    avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user))
+                }
+                Err(e) => HttpResponse::build(e.status_code()).json(e),
             }
-            existing_user.date_update = Utc::now();
-            // Le rôle n'est pas modifié, il reste celui défini par défaut
+        }
+        Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
 
-            match repo.mettre_a_jour(&existing_user).await {
-                Ok(user) => HttpResponse::Ok().json(user),
+fn correctif(req: &HttpRequest, corps: &[u8]) -> Result<Correctif, MyError> {
+    let type_contenu = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|valeur| valeur.to_str().ok())
+        .and_then(|valeur| valeur.split(';').next())
+        .map(|valeur| valeur.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let illisible = |e: serde_json::Error| MyError::BadRequest(e.to_string());
+    match type_contenu.as_str() {
+        "application/merge-patch+json" => Ok(Correctif::Fusion(serde_json::from_slice(corps).map_err(illisible)?)),
+        "application/json-patch+json" => Ok(Correctif::Operations(serde_json::from_slice(corps).map_err(illisible)?)),
+        _ => Err(MyError::TypeNonSupporte(
+            "PATCH attend application/merge-patch+json ou application/json-patch+json".to_string(),
+        )),
+    }
+}
+
+// PATCH : correctif appliqué au document modifiable, puis validé comme un PUT
+pub async fn modifier(
+    req: HttpRequest,
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    version: VersionAttendue,
+    path: web::Path<Uuid>,
+    services: ServicesCompte,
+    corps: web::Bytes,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::ModifierUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    let correctif = match correctif(&req, &corps) {
+        Ok(correctif) => correctif,
+        Err(e) => return HttpResponse::build(e.status_code()).json(e),
+    };
+    match services.repo.obtenir_par_id(id).await {
+        Ok(Some(existing_user)) => {
+            let modification = match correctif.appliquer(&existing_user) {
+                Ok(modification) => modification,
+                Err(e) => return HttpResponse::build(e.status_code()).json(e),
+            };
+            let avant = existing_user.clone();
+            let resultat = remplacer(
+                &auth,
+                existing_user,
+                modification,
+                &version.0,
+                &services,
+            )
+            .await;
+            match resultat {
+                Ok(user) => {
+                    auditeur.consigner(
+                        EntreeAudit::new(ActionAudit::ModificationUtilisateur, Some(auth.utilisateur.id), Some(user.id))
+                            .avec_changements(changements_utilisateur(Some(&avant), Some(&user))),
+                    ).await;
+                    avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user))
+                }
                 Err(e) => HttpResponse::build(e.status_code()).json(e),
             }
         }
@@ -98,12 +323,159 @@ pub async fn mettre_a_jour(
     }
 }
 
+pub async fn changer_role(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    version: VersionAttendue,
+    path: web::Path<Uuid>,
+    repo: web::Data<dyn UtilisateurEntree>,
+    changement: web::Json<ChangerRole>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger(Permission::GererRoles) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    // État précédent, pour le journal d'audit
+    let avant = match repo.obtenir_par_id(id).await {
+        Ok(avant) => avant,
+        Err(e) => return HttpResponse::build(e.status_code()).json(e),
+    };
+    match repo.changer_role(id, changement.role, &version.0).await {
+        Ok(user) => {
+            auditeur.consigner(
+                EntreeAudit::new(ActionAudit::ChangementRole, Some(auth.utilisateur.id), Some(user.id))
+                    .avec_changements(changements_utilisateur(avant.as_ref(), Some(&user))),
+            ).await;
+            avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user))
+        }
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
+
+// Lève le verrouillage après trop d'échecs de connexion
+pub async fn deverrouiller(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    path: web::Path<Uuid>,
+    repo: web::Data<dyn UtilisateurEntree>,
+    tentatives: web::Data<dyn TentativesPort>,
+    regles_email: web::Data<ReglesEmail>,
+) -> impl Responder {
+    if let Err(e) = auth.exiger(Permission::ModifierUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match repo.obtenir_par_id(path.into_inner()).await {
+        Ok(Some(user)) => match tentatives.effacer(&cle_compte(&user.email, &regles_email)).await {
+            Ok(()) => {
+                auditeur.consigner(
+                    EntreeAudit::new(ActionAudit::DeverrouillageUtilisateur, Some(auth.utilisateur.id), Some(user.id)),
+                ).await;
+                HttpResponse::NoContent().finish()
+            }
+            Err(e) => HttpResponse::build(e.status_code()).json(e),
+        },
+        Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
+
+// Archive RGPD téléchargeable de tout ce qui est rattaché au compte
+pub async fn exporter(
+    auth: UtilisateurAuthentifie,
+    path: web::Path<Uuid>,
+    donnees: web::Data<dyn DonneesPersonnellesPort>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::ExporterDonnees) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    if let Err(e) = auth.interdire_en_usurpation(ActionSensible::ExportDonnees) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match donnees.exporter(id).await {
+        Ok(Some(export)) => HttpResponse::Ok()
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"donnees-{}.json\"", id),
+            ))
+            .json(ExportDonnees::new(id, export)),
+        Ok(None) => HttpResponse::NotFound().json(MyError::NotFound("Utilisateur non trouvé".to_string())),
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
+
+// Suppression douce : connexion impossible, compte restaurable jusqu'à la purge
 pub async fn supprimer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    version: VersionAttendue,
+    path: web::Path<Uuid>,
+    repo: web::Data<dyn UtilisateurEntree>,
+    sessions: web::Data<dyn SessionPort>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::SupprimerUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    if let Err(e) = auth.interdire_en_usurpation(ActionSensible::SuppressionCompte) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    if let Err(e) = repo.supprimer(id, &version.0).await {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    auditeur.consigner(EntreeAudit::new(ActionAudit::SuppressionUtilisateur, Some(auth.utilisateur.id), Some(id))).await;
+    match sessions.revoquer_tout(id).await {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
+
+pub async fn restaurer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
     path: web::Path<Uuid>,
     repo: web::Data<dyn UtilisateurEntree>,
 ) -> impl Responder {
-    match repo.supprimer(path.into_inner()).await {
-        Ok(()) => HttpResponse::NoContent().finish(),
+    if let Err(e) = auth.exiger(Permission::GererSuppressions) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    match repo.restaurer(path.into_inner()).await {
+        Ok(user) => {
+            auditeur.consigner(
+                EntreeAudit::new(ActionAudit::RestaurationUtilisateur, Some(auth.utilisateur.id), Some(user.id)),
+            ).await;
+            avec_etag(HttpResponse::Ok(), &user).json(VueUtilisateur::pour(&auth.utilisateur, &user))
+        }
+        Err(e) => HttpResponse::build(e.status_code()).json(e),
+    }
+}
+
+// Droit à l'effacement, sans attendre la purge : anonymisation du compte,
+// les commandes sont conservées pour la comptabilité
+pub async fn effacer(
+    auth: UtilisateurAuthentifie,
+    auditeur: Auditeur,
+    version: VersionAttendue,
+    path: web::Path<Uuid>,
+    hacheur: web::Data<HacheurMotDePasse>,
+    donnees: web::Data<dyn DonneesPersonnellesPort>,
+) -> impl Responder {
+    let id = path.into_inner();
+    if let Err(e) = auth.exiger_soi_ou(id, Permission::SupprimerUtilisateurs) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    if let Err(e) = auth.interdire_en_usurpation(ActionSensible::SuppressionCompte) {
+        return HttpResponse::build(e.status_code()).json(e);
+    }
+    let anonymisation = match Anonymisation::pour(id, &hacheur).await {
+        Ok(anonymisation) => anonymisation,
+        Err(e) => return HttpResponse::build(e.status_code()).json(e),
+    };
+    match donnees.effacer(id, &anonymisation, &version.0).await {
+        Ok(()) => {
+            auditeur.consigner(EntreeAudit::new(ActionAudit::EffacementUtilisateur, Some(auth.utilisateur.id), Some(id))).await;
+            HttpResponse::NoContent().finish()
+        }
         Err(e) => HttpResponse::build(e.status_code()).json(e),
     }
 }
@@ -117,8 +489,14 @@ pub fn configurer_routes(cfg: &mut web::ServiceConfig) {
             .route("", web::post().to(creer))
             .route("/nom/{nom}", web::get().to(obtenir_par_nom))
             .route("/email/{email}", web::get().to(obtenir_par_email))
-            .route("", web::get().to(obtenir_tous))
+            .route("", web::get().to(lister))
             .route("/{id}", web::put().to(mettre_a_jour))
+            .route("/{id}", web::patch().to(modifier))
+            .route("/{id}/role", web::put().to(changer_role))
+            .route("/{id}/deverrouiller", web::post().to(deverrouiller))
+            .route("/{id}/export", web::get().to(exporter))
+            .route("/{id}/effacer", web::post().to(effacer))
+            .route("/{id}/restaurer", web::post().to(restaurer))
             .route("/{id}", web::delete().to(supprimer))
     );
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+use crate::ports::audit::AuditPort;
+use crate::domain::audit::{EntreeAudit, RequeteAudit};
+use crate::domain::pagination::{limite_effective, Curseur, Page};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlAudit {
+    pool: PgPool,
+}
+
+impl PostgreSqlAudit {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn appliquer_filtres(qb: &mut QueryBuilder<'_, Postgres>, requete: &RequeteAudit) {
+    qb.push(" WHERE TRUE");
+    if let Some(acteur_id) = requete.acteur_id {
+        qb.push(" AND acteur_id = ").push_bind(acteur_id);
+    }
+    if let Some(cible_id) = requete.cible_id {
+        qb.push(" AND cible_id = ").push_bind(cible_id);
+    }
+    if let Some(usurpateur_id) = requete.usurpateur_id {
+        qb.push(" AND usurpateur_id = ").push_bind(usurpateur_id);
+    }
+    if let Some(action) = requete.action {
+        qb.push(" AND action = ").push_bind(action);
+    }
+    if let Some(type_cible) = &requete.type_cible {
+        qb.push(" AND type_cible = ").push_bind(type_cible.clone());
+    }
+    if let Some(requete_id) = &requete.requete_id {
+        qb.push(" AND requete_id = ").push_bind(requete_id.clone());
+    }
+    if let Some(depuis) = requete.depuis {
+        qb.push(" AND date >= ").push_bind(depuis);
+    }
+    if let Some(jusqu_a) = requete.jusqu_a {
+        qb.push(" AND date < ").push_bind(jusqu_a);
+    }
+}
+
+
+#[async_trait]
+impl AuditPort for PostgreSqlAudit {
+    async fn enregistrer(&self, entree: &EntreeAudit) -> Result<(), MyError> {
+        sqlx::query(
+            "INSERT INTO journal_audit (id, date, acteur_id, action, type_cible, cible_id, usurpateur_id, changements, requete_id, adresse_ip)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+        )
+        .bind(entree.id)
+        .bind(entree.date)
+        .bind(entree.acteur_id)
+        .bind(entree.action)
+        .bind(&entree.type_cible)
+        .bind(entree.cible_id)
+        .bind(entree.usurpateur_id)
+        .bind(&entree.changements)
+        .bind(&entree.requete_id)
+        .bind(&entree.adresse_ip)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn lister(&self, requete: &RequeteAudit) -> Result<Page<EntreeAudit>, MyError> {
+        let limite = limite_effective(requete.limite);
+
+        let mut compte = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM journal_audit");
+        appliquer_filtres(&mut compte, requete);
+        let (total,): (i64,) = compte
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, date, acteur_id, action, type_cible, cible_id, usurpateur_id, changements, requete_id, adresse_ip FROM journal_audit"
+        );
+        appliquer_filtres(&mut qb, requete);
+        if let Some(curseur) = &requete.curseur {
+            // Du plus récent au plus ancien : on reprend strictement avant le dernier élément vu
+            let curseur = Curseur::decoder(curseur)?;
+            qb.push(" AND (date, id) < (CAST(")
+                .push_bind(curseur.valeur)
+                .push(" AS TIMESTAMPTZ), ")
+                .push_bind(curseur.id)
+                .push(")");
+        }
+        qb.push(" ORDER BY date DESC, id DESC LIMIT ").push_bind(i64::from(limite) + 1);
+
+        let mut entrees = qb
+            .build_query_as::<EntreeAudit>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let curseur_suivant = if entrees.len() > limite as usize {
+            entrees.truncate(limite as usize);
+            entrees.last().map(|derniere| Curseur {
+                valeur: derniere.date.to_rfc3339(),
+                id: derniere.id,
+            }.encoder())
+        } else {
+            None
+        };
+
+        Ok(Page { elements: entrees, total, limite, curseur_suivant })
+    }
+}
@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Error as SqlxError};
+use uuid::Uuid;
+
+use crate::ports::categories::CategoriePort;
+use crate::domain::models::Categorie;
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlCategories {
+    pool: PgPool,
+}
+
+impl PostgreSqlCategories {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// Contraintes posées par la migration de l'arborescence ; tout le reste est une erreur de base
+const CLE_PARENT: &str = "categories_parent_id_fkey";
+const PARENT_DISTINCT: &str = "categories_parent_distinct";
+
+fn erreur_parent(e: SqlxError) -> MyError {
+    match e {
+        SqlxError::Database(db_err) if db_err.constraint() == Some(CLE_PARENT) => {
+            MyError::BadRequest("Catégorie parente inexistante".to_string())
+        }
+        SqlxError::Database(db_err) if db_err.constraint() == Some(PARENT_DISTINCT) => {
+            MyError::BadRequest("Une catégorie ne peut pas être son propre parent".to_string())
+        }
+        _ => MyError::Database(e.to_string()),
+    }
+}
+
+
+#[async_trait]
+impl CategoriePort for PostgreSqlCategories {
+    async fn creer(&self, categorie: &Categorie) -> Result<Categorie, MyError> {
+        let categorie = sqlx::query_as::<_, Categorie>(
+            r#"
+            INSERT INTO categories (id, nom, description, parent_id, date_creation)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, nom, description, parent_id, date_creation
+            "#,
+        )
+        .bind(categorie.id)
+        .bind(&categorie.nom)
+        .bind(&categorie.description)
+        .bind(categorie.parent_id)
+        .bind(categorie.date_creation)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(erreur_parent)?;
+
+        Ok(categorie)
+    }
+
+    async fn obtenir(&self, id: Uuid) -> Result<Option<Categorie>, MyError> {
+        let categorie = sqlx::query_as::<_, Categorie>(
+            "SELECT id, nom, description, parent_id, date_creation FROM categories WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(categorie)
+    }
+
+    async fn lister(&self) -> Result<Vec<Categorie>, MyError> {
+        let categories = sqlx::query_as::<_, Categorie>(
+            "SELECT id, nom, description, parent_id, date_creation FROM categories ORDER BY nom, id"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(categories)
+    }
+
+    async fn mettre_a_jour(&self, categorie: &Categorie) -> Result<Categorie, MyError> {
+        let categorie = sqlx::query_as::<_, Categorie>(
+            r#"
+            UPDATE categories
+            SET nom = $2, description = $3
+            WHERE id = $1
+            RETURNING id, nom, description, parent_id, date_creation
+            "#,
+        )
+        .bind(categorie.id)
+        .bind(&categorie.nom)
+        .bind(&categorie.description)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        categorie.ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))
+    }
+
+    async fn deplacer(&self, id: Uuid, parent_id: Option<Uuid>) -> Result<Categorie, MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        // Deux déplacements concurrents, chacun valide seul, pourraient former un cycle : ils passent l'un après l'autre
+        sqlx::query("LOCK TABLE categories IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        if let Some(parent_id) = parent_id {
+            // Le nouveau parent ne doit pas compter la catégorie parmi ses ancêtres (ni être elle-même)
+            let (cycle,): (bool,) = sqlx::query_as(
+                r#"
+                WITH RECURSIVE ancetres AS (
+                    SELECT id, parent_id FROM categories WHERE id = $1
+                    UNION ALL
+                    SELECT c.id, c.parent_id FROM categories c JOIN ancetres a ON c.id = a.parent_id
+                )
+                SELECT EXISTS (SELECT 1 FROM ancetres WHERE id = $2)
+                "#,
+            )
+            .bind(parent_id)
+            .bind(id)
+            .fetch_one(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+            if cycle {
+                return Err(MyError::BadRequest(
+                    "Une catégorie ne peut pas être placée sous elle-même ou sous l'une de ses sous-catégories".to_string(),
+                ));
+            }
+        }
+
+        let categorie = sqlx::query_as::<_, Categorie>(
+            r#"
+            UPDATE categories
+            SET parent_id = $2
+            WHERE id = $1
+            RETURNING id, nom, description, parent_id, date_creation
+            "#,
+        )
+        .bind(id)
+        .bind(parent_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(erreur_parent)?
+        .ok_or_else(|| MyError::NotFound("Catégorie non trouvée".to_string()))?;
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(categorie)
+    }
+
+    async fn supprimer(&self, id: Uuid) -> Result<(), MyError> {
+        let result = sqlx::query("DELETE FROM categories WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                SqlxError::Database(db_err) if db_err.constraint() == Some(CLE_PARENT) => MyError::BadRequest(
+                    "Catégorie non vide : déplacer ou supprimer d'abord ses sous-catégories".to_string(),
+                ),
+                _ => MyError::Database(e.to_string()),
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(MyError::NotFound("Catégorie non trouvée".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn fil_ariane(&self, id: Uuid) -> Result<Vec<Categorie>, MyError> {
+        let categories = sqlx::query_as::<_, Categorie>(
+            r#"
+            WITH RECURSIVE ancetres AS (
+                SELECT id, nom, description, parent_id, date_creation, 0 AS profondeur
+                FROM categories WHERE id = $1
+                UNION ALL
+                SELECT c.id, c.nom, c.description, c.parent_id, c.date_creation, a.profondeur + 1
+                FROM categories c JOIN ancetres a ON c.id = a.parent_id
+            )
+            SELECT id, nom, description, parent_id, date_creation FROM ancetres ORDER BY profondeur DESC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(categories)
+    }
+}
@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::ports::cles_api::ClesApiPort;
+use crate::domain::cle_api::CleApi;
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlClesApi {
+    pool: PgPool,
+}
+
+impl PostgreSqlClesApi {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl ClesApiPort for PostgreSqlClesApi {
+    async fn creer(&self, cle: &CleApi) -> Result<CleApi, MyError> {
+        let cle = sqlx::query_as::<_, CleApi>(
+            r#"
+            INSERT INTO cles_api (id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration,
+                      date_derniere_utilisation, date_revocation, date_creation
+            "#,
+        )
+        .bind(cle.id)
+        .bind(cle.utilisateur_id)
+        .bind(&cle.nom)
+        .bind(&cle.prefixe)
+        .bind(&cle.cle_hachee)
+        .bind(&cle.portees)
+        .bind(cle.date_expiration)
+        .bind(cle.date_creation)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(cle)
+    }
+
+    async fn lister(&self, utilisateur_id: Uuid) -> Result<Vec<CleApi>, MyError> {
+        let cles = sqlx::query_as::<_, CleApi>(
+            "SELECT id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration,
+                    date_derniere_utilisation, date_revocation, date_creation
+             FROM cles_api WHERE utilisateur_id = $1 ORDER BY date_creation DESC"
+        )
+        .bind(utilisateur_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(cles)
+    }
+
+    async fn obtenir(&self, id: Uuid) -> Result<Option<CleApi>, MyError> {
+        let cle = sqlx::query_as::<_, CleApi>(
+            "SELECT id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration,
+                    date_derniere_utilisation, date_revocation, date_creation
+             FROM cles_api WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(cle)
+    }
+
+    async fn obtenir_valide(&self, cle_hachee: &str) -> Result<Option<CleApi>, MyError> {
+        let cle = sqlx::query_as::<_, CleApi>(
+            "SELECT id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration,
+                    date_derniere_utilisation, date_revocation, date_creation
+             FROM cles_api
+             WHERE cle_hachee = $1 AND date_revocation IS NULL
+             AND (date_expiration IS NULL OR date_expiration > NOW())"
+        )
+        .bind(cle_hachee)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(cle)
+    }
+
+    async fn marquer_utilisation(&self, id: Uuid) -> Result<(), MyError> {
+        // Au plus une écriture par minute et par clé
+        sqlx::query(
+            "UPDATE cles_api SET date_derniere_utilisation = NOW()
+             WHERE id = $1
+             AND (date_derniere_utilisation IS NULL OR date_derniere_utilisation < NOW() - INTERVAL '1 minute')"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn revoquer(&self, id: Uuid) -> Result<CleApi, MyError> {
+        sqlx::query_as::<_, CleApi>(
+            r#"
+            UPDATE cles_api SET date_revocation = COALESCE(date_revocation, NOW())
+            WHERE id = $1
+            RETURNING id, utilisateur_id, nom, prefixe, cle_hachee, portees, date_expiration,
+                      date_derniere_utilisation, date_revocation, date_creation
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?
+        .ok_or_else(|| MyError::NotFound("Clé d'API non trouvée".to_string()))
+    }
+}
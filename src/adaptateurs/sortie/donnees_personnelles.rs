@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::ports::donnees_personnelles::DonneesPersonnellesPort;
+use crate::ports::users::UtilisateurEntree;
+use crate::ports::audit::AuditPort;
+use crate::domain::donnees_personnelles::{Anonymisation, ConfigRetention};
+use crate::domain::mot_de_passe::HacheurMotDePasse;
+use crate::domain::concurrence::Precondition;
+use crate::domain::tentatives::{cle_compte, cle_verification_telephone};
+use crate::domain::email::ReglesEmail;
+use crate::domain::audit::{ActionAudit, EntreeAudit};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlDonneesPersonnelles {
+    pool: PgPool,
+    regles: ReglesEmail,
+}
+
+impl PostgreSqlDonneesPersonnelles {
+
+    pub fn new(pool: PgPool, regles: ReglesEmail) -> Self {
+        Self { pool, regles }
+    }
+}
+
+// Tâche de fond qui efface définitivement les comptes supprimés depuis plus que la rétention
+pub fn lancer_purge(
+    utilisateurs: Arc<dyn UtilisateurEntree>,
+    donnees: Arc<dyn DonneesPersonnellesPort>,
+    hacheur: Arc<HacheurMotDePasse>,
+    audit: Arc<dyn AuditPort>,
+    config: ConfigRetention,
+) {
+    actix_web::rt::spawn(async move {
+        let mut minuterie = actix_web::rt::time::interval(config.intervalle_purge);
+        loop {
+            minuterie.tick().await;
+            let ids = match utilisateurs.supprimes_avant(Utc::now() - config.duree).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    tracing::error!("Purge des comptes supprimés impossible: {}", e);
+                    continue;
+                }
+            };
+            for id in ids {
+                let resultat = match Anonymisation::pour(id, &hacheur).await {
+                    Ok(anonymisation) => donnees.effacer(id, &anonymisation, &Precondition::Toute).await,
+                    Err(e) => Err(e),
+                };
+                match resultat {
+                    Ok(()) => {
+                        tracing::info!("Compte supprimé {} purgé", id);
+                        // Action système : pas d'acteur
+                        let entree = EntreeAudit::new(ActionAudit::EffacementUtilisateur, None, Some(id));
+                        if let Err(e) = audit.enregistrer(&entree).await {
+                            tracing::error!("Entrée d'audit de la purge de {} non enregistrée: {}", id, e);
+                        }
+                    }
+                    Err(e) => tracing::error!("Purge du compte {} impossible: {}", id, e),
+                }
+            }
+        }
+    });
+}
+
+// Une entrée par table rattachée au compte ; mots de passe, tokens et secrets n'y figurent pas
+const REQUETE_EXPORT: &str = r#"
+SELECT json_build_object(
+    'utilisateur', (
+        SELECT row_to_json(u) FROM (
+            SELECT id, email, prenom, nom, role, email_verifie, date_creation, date_update
+            FROM utilisateur WHERE id = $1
+        ) u
+    ),
+    'adresses', COALESCE((
+        SELECT json_agg(a ORDER BY a.date_creation) FROM addresses a WHERE a.utilisateur_id = $1
+    ), '[]'::json),
+    'commandes', COALESCE((
+        SELECT json_agg(c ORDER BY c.date_creation) FROM (
+            SELECT o.*, COALESCE((
+                SELECT json_agg(l) FROM order_items l WHERE l.order_id = o.id
+            ), '[]'::json) AS lignes
+            FROM orders o WHERE o.utilisateur_id = $1
+        ) c
+    ), '[]'::json),
+    'avis', COALESCE((
+        SELECT json_agg(r ORDER BY r.date_creation) FROM reviews r WHERE r.utilisateur_id = $1
+    ), '[]'::json),
+    'liste_envies', COALESCE((
+        SELECT json_agg(w ORDER BY w.date_ajout) FROM wishlist w WHERE w.utilisateur_id = $1
+    ), '[]'::json),
+    'panier', COALESCE((
+        SELECT json_agg(p ORDER BY p.date_creation) FROM cart_items p WHERE p.utilisateur_id = $1
+    ), '[]'::json),
+    'notifications', COALESCE((
+        SELECT json_agg(n ORDER BY n.date_creation) FROM notifications n WHERE n.utilisateur_id = $1
+    ), '[]'::json),
+    'sessions', COALESCE((
+        SELECT json_agg(s ORDER BY s.date_creation) FROM (
+            SELECT id, appareil, adresse_ip, derniere_activite, date_creation, date_expiration
+            FROM sessions WHERE utilisateur_id = $1
+        ) s
+    ), '[]'::json),
+    'double_facteur', (
+        SELECT row_to_json(d) FROM (
+            SELECT actif, date_activation, date_creation FROM double_facteur WHERE utilisateur_id = $1
+        ) d
+    ),
+    'cles_api', COALESCE((
+        SELECT json_agg(k ORDER BY k.date_creation) FROM (
+            SELECT id, nom, prefixe, portees, date_expiration, date_derniere_utilisation, date_revocation, date_creation
+            FROM cles_api WHERE utilisateur_id = $1
+        ) k
+    ), '[]'::json),
+    'identites_externes', COALESCE((
+        SELECT json_agg(i ORDER BY i.date_creation) FROM (
+            SELECT fournisseur, sujet, email, date_derniere_connexion, date_creation
+            FROM identites_externes WHERE utilisateur_id = $1
+        ) i
+    ), '[]'::json),
+    'telephone', (
+        SELECT row_to_json(t) FROM (
+            SELECT numero, date_verification, date_creation FROM telephones WHERE utilisateur_id = $1
+        ) t
+    ),
+    'journal_audit', COALESCE((
+        SELECT json_agg(j ORDER BY j.date) FROM (
+            SELECT date, action, type_cible, cible_id, acteur_id, usurpateur_id, changements, adresse_ip
+            FROM journal_audit WHERE acteur_id = $1 OR cible_id = $1
+        ) j
+    ), '[]'::json)
+)
+FROM utilisateur WHERE id = $1 AND date_effacement IS NULL
+"#;
+
+// Données sans intérêt comptable : supprimées à l'effacement
+const SUPPRESSIONS: &[&str] = &[
+    "DELETE FROM cart_items WHERE utilisateur_id = $1
+     OR session_id IN (SELECT id FROM sessions WHERE utilisateur_id = $1)",
+    "DELETE FROM sessions WHERE utilisateur_id = $1",
+    "DELETE FROM wishlist WHERE utilisateur_id = $1",
+    "DELETE FROM reviews WHERE utilisateur_id = $1",
+    "DELETE FROM notifications WHERE utilisateur_id = $1",
+    "DELETE FROM jetons_usage_unique WHERE utilisateur_id = $1",
+    "DELETE FROM codes_recuperation WHERE utilisateur_id = $1",
+    "DELETE FROM double_facteur WHERE utilisateur_id = $1",
+    "DELETE FROM cles_api WHERE utilisateur_id = $1",
+    "DELETE FROM identites_externes WHERE utilisateur_id = $1",
+    "DELETE FROM codes_sms WHERE utilisateur_id = $1",
+    "DELETE FROM telephones WHERE utilisateur_id = $1",
+    // Les adresses d'une commande restent, réduites à ce qui sert à la facturation
+    "DELETE FROM addresses a WHERE a.utilisateur_id = $1
+     AND NOT EXISTS (SELECT 1 FROM orders o WHERE o.lieu_publique_proche = a.id)",
+    "UPDATE addresses SET prenom = 'Anonyme', nom = 'Anonyme', ligne1 = '' WHERE utilisateur_id = $1",
+];
+
+
+
+#[async_trait]
+impl DonneesPersonnellesPort for PostgreSqlDonneesPersonnelles {
+    async fn exporter(&self, utilisateur_id: Uuid) -> Result<Option<Value>, MyError> {
+        let export: Option<(Value,)> = sqlx::query_as(REQUETE_EXPORT)
+            .bind(utilisateur_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(export.map(|(donnees,)| donnees))
+    }
+
+    async fn effacer(
+        &self,
+        utilisateur_id: Uuid,
+        anonymisation: &Anonymisation,
+        precondition: &Precondition,
+    ) -> Result<(), MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        let compte: Option<(String, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT email, date_update FROM utilisateur
+             WHERE id = $1 AND date_effacement IS NULL FOR UPDATE"
+        )
+        .bind(utilisateur_id)
+        .fetch_optional(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+        let (email, date_update) = compte.ok_or_else(|| MyError::NotFound("Utilisateur non trouvé".to_string()))?;
+        precondition.verifier(date_update)?;
+
+        for requete in SUPPRESSIONS {
+            sqlx::query(requete)
+                .bind(utilisateur_id)
+                .execute(&mut tx)
+                .await
+                .map_err(|e| MyError::Database(e.to_string()))?;
+        }
+
+        sqlx::query("DELETE FROM tentatives_connexion WHERE cle = ANY($1)")
+            .bind(vec![cle_compte(&email, &self.regles), cle_verification_telephone(utilisateur_id)])
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        // Sans commande ni adresse de facturation, rien n'impose de garder le compte
+        let supprime = sqlx::query(
+            "DELETE FROM utilisateur u WHERE u.id = $1
+             AND NOT EXISTS (SELECT 1 FROM orders o WHERE o.utilisateur_id = u.id)
+             AND NOT EXISTS (SELECT 1 FROM addresses a WHERE a.utilisateur_id = u.id)"
+        )
+        .bind(utilisateur_id)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        if supprime.rows_affected() == 0 {
+            sqlx::query(
+                r#"
+                UPDATE utilisateur
+                SET email = $2, email_cle = $6, prenom = $3, nom = $4, mot_de_passe = $5, email_verifie = FALSE,
+                    role = 'client', date_effacement = NOW(), date_update = NOW(),
+                    date_suppression = COALESCE(date_suppression, NOW())
+                WHERE id = $1
+                "#,
+            )
+            .bind(utilisateur_id)
+            .bind(&anonymisation.email)
+            .bind(&anonymisation.prenom)
+            .bind(&anonymisation.nom)
+            .bind(&anonymisation.mot_de_passe)
+            .bind(self.regles.cle(&anonymisation.email))
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+        }
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
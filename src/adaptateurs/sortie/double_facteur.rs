@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::ports::double_facteur::DoubleFacteurPort;
+use crate::domain::totp::DoubleFacteur;
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlDoubleFacteur {
+    pool: PgPool,
+}
+
+impl PostgreSqlDoubleFacteur {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl DoubleFacteurPort for PostgreSqlDoubleFacteur {
+    async fn obtenir(&self, utilisateur_id: Uuid) -> Result<Option<DoubleFacteur>, MyError> {
+        let facteur = sqlx::query_as::<_, DoubleFacteur>(
+            "SELECT utilisateur_id, secret, actif, dernier_pas, date_activation, date_creation
+             FROM double_facteur WHERE utilisateur_id = $1"
+        )
+        .bind(utilisateur_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(facteur)
+    }
+
+    async fn enregistrer(&self, facteur: &DoubleFacteur) -> Result<(), MyError> {
+        sqlx::query(
+            r#"
+            INSERT INTO double_facteur (utilisateur_id, secret, actif, dernier_pas, date_activation, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (utilisateur_id) DO UPDATE
+            SET secret = EXCLUDED.secret, actif = EXCLUDED.actif, dernier_pas = EXCLUDED.dernier_pas,
+                date_activation = EXCLUDED.date_activation, date_creation = EXCLUDED.date_creation
+            "#,
+        )
+        .bind(facteur.utilisateur_id)
+        .bind(&facteur.secret)
+        .bind(facteur.actif)
+        .bind(facteur.dernier_pas)
+        .bind(facteur.date_activation)
+        .bind(facteur.date_creation)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn activer(&self, utilisateur_id: Uuid, pas: i64, codes_haches: &[String]) -> Result<(), MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query(
+            "UPDATE double_facteur SET actif = TRUE, dernier_pas = $2, date_activation = NOW()
+             WHERE utilisateur_id = $1"
+        )
+        .bind(utilisateur_id)
+        .bind(pas)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query("DELETE FROM codes_recuperation WHERE utilisateur_id = $1")
+            .bind(utilisateur_id)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        for code_hache in codes_haches {
+            sqlx::query("INSERT INTO codes_recuperation (id, utilisateur_id, code_hache) VALUES ($1, $2, $3)")
+                .bind(Uuid::new_v4())
+                .bind(utilisateur_id)
+                .bind(code_hache)
+                .execute(&mut tx)
+                .await
+                .map_err(|e| MyError::Database(e.to_string()))?;
+        }
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn marquer_pas(&self, utilisateur_id: Uuid, pas: i64) -> Result<bool, MyError> {
+        let result = sqlx::query(
+            "UPDATE double_facteur SET dernier_pas = $2
+             WHERE utilisateur_id = $1 AND (dernier_pas IS NULL OR dernier_pas < $2)"
+        )
+        .bind(utilisateur_id)
+        .bind(pas)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn consommer_code_recuperation(&self, utilisateur_id: Uuid, code_hache: &str) -> Result<bool, MyError> {
+        let result = sqlx::query(
+            "UPDATE codes_recuperation SET date_utilisation = NOW()
+             WHERE utilisateur_id = $1 AND code_hache = $2 AND date_utilisation IS NULL"
+        )
+        .bind(utilisateur_id)
+        .bind(code_hache)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn desactiver(&self, utilisateur_id: Uuid) -> Result<(), MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query("DELETE FROM codes_recuperation WHERE utilisateur_id = $1")
+            .bind(utilisateur_id)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query("DELETE FROM double_facteur WHERE utilisateur_id = $1")
+            .bind(utilisateur_id)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
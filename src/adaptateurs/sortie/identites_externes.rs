@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{PgPool, Error as SqlxError};
+use uuid::Uuid;
+
+use crate::ports::oidc::IdentitesExternesPort;
+use crate::domain::oidc::{DemandeOidc, IdentiteExterne};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlIdentitesExternes {
+    pool: PgPool,
+}
+
+impl PostgreSqlIdentitesExternes {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl IdentitesExternesPort for PostgreSqlIdentitesExternes {
+    async fn creer_demande(&self, demande: &DemandeOidc) -> Result<(), MyError> {
+        // Les demandes abandonnées sont nettoyées au passage
+        sqlx::query("DELETE FROM demandes_oidc WHERE date_expiration < NOW()")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO demandes_oidc (etat_hache, fournisseur, verificateur_pkce, nonce, date_expiration, date_creation)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        )
+        .bind(&demande.etat_hache)
+        .bind(&demande.fournisseur)
+        .bind(&demande.verificateur_pkce)
+        .bind(&demande.nonce)
+        .bind(demande.date_expiration)
+        .bind(demande.date_creation)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn consommer_demande(&self, etat_hache: &str) -> Result<Option<DemandeOidc>, MyError> {
+        let demande = sqlx::query_as::<_, DemandeOidc>(
+            "DELETE FROM demandes_oidc WHERE etat_hache = $1
+             RETURNING etat_hache, fournisseur, verificateur_pkce, nonce, date_expiration, date_creation"
+        )
+        .bind(etat_hache)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(demande.filter(|demande| demande.date_expiration > Utc::now()))
+    }
+
+    async fn obtenir(&self, fournisseur: &str, sujet: &str) -> Result<Option<IdentiteExterne>, MyError> {
+        let identite = sqlx::query_as::<_, IdentiteExterne>(
+            "SELECT id, utilisateur_id, fournisseur, sujet, email, date_derniere_connexion, date_creation
+             FROM identites_externes WHERE fournisseur = $1 AND sujet = $2"
+        )
+        .bind(fournisseur)
+        .bind(sujet)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(identite)
+    }
+
+    async fn lier(&self, identite: &IdentiteExterne) -> Result<IdentiteExterne, MyError> {
+        let identite = sqlx::query_as::<_, IdentiteExterne>(
+            r#"
+            INSERT INTO identites_externes (id, utilisateur_id, fournisseur, sujet, email, date_derniere_connexion, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, utilisateur_id, fournisseur, sujet, email, date_derniere_connexion, date_creation
+            "#,
+        )
+        .bind(identite.id)
+        .bind(identite.utilisateur_id)
+        .bind(&identite.fournisseur)
+        .bind(&identite.sujet)
+        .bind(&identite.email)
+        .bind(identite.date_derniere_connexion)
+        .bind(identite.date_creation)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::Database(db_err) if db_err.constraint().is_some() => {
+                MyError::BadRequest("Identité déjà rattachée à un compte".to_string())
+            }
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        Ok(identite)
+    }
+
+    async fn toucher(&self, id: Uuid) -> Result<(), MyError> {
+        sqlx::query("UPDATE identites_externes SET date_derniere_connexion = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::ports::jetons::JetonPort;
+use crate::domain::jeton::{JetonUsageUnique, ObjetJeton};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlJeton {
+    pool: PgPool,
+}
+
+impl PostgreSqlJeton {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl JetonPort for PostgreSqlJeton {
+    async fn creer(&self, jeton: &JetonUsageUnique) -> Result<(), MyError> {
+        sqlx::query(
+            r#"
+            INSERT INTO jetons_usage_unique (id, utilisateur_id, objet, jeton_hache, date_expiration, date_utilisation, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(jeton.id)
+        .bind(jeton.utilisateur_id)
+        .bind(jeton.objet)
+        .bind(&jeton.jeton_hache)
+        .bind(jeton.date_expiration)
+        .bind(jeton.date_utilisation)
+        .bind(jeton.date_creation)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn consommer(&self, objet: ObjetJeton, jeton_hache: &str) -> Result<Option<Uuid>, MyError> {
+        // Un seul UPDATE : deux requêtes concurrentes ne peuvent pas utiliser le même jeton
+        let utilisateur_id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE jetons_usage_unique
+            SET date_utilisation = NOW()
+            WHERE objet = $1 AND jeton_hache = $2
+              AND date_utilisation IS NULL AND date_expiration > NOW()
+            RETURNING utilisateur_id
+            "#,
+        )
+        .bind(objet)
+        .bind(jeton_hache)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(utilisateur_id)
+    }
+
+    async fn marquer_utilise(&self, jeton: &JetonUsageUnique) -> Result<bool, MyError> {
+        // L'unicité de jeton_hache départage deux échanges concurrents
+        let result = sqlx::query(
+            r#"
+            INSERT INTO jetons_usage_unique (id, utilisateur_id, objet, jeton_hache, date_expiration, date_utilisation, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (jeton_hache) DO NOTHING
+            "#,
+        )
+        .bind(jeton.id)
+        .bind(jeton.utilisateur_id)
+        .bind(jeton.objet)
+        .bind(&jeton.jeton_hache)
+        .bind(jeton.date_expiration)
+        .bind(jeton.date_utilisation)
+        .bind(jeton.date_creation)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoquer_tous(&self, utilisateur_id: Uuid, objet: ObjetJeton) -> Result<(), MyError> {
+        sqlx::query(
+            "UPDATE jetons_usage_unique SET date_utilisation = NOW()
+             WHERE utilisateur_id = $1 AND objet = $2 AND date_utilisation IS NULL"
+        )
+        .bind(utilisateur_id)
+        .bind(objet)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
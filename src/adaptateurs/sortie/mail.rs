@@ -0,0 +1,117 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use uuid::Uuid;
+
+use crate::ports::mail::MailPort;
+use crate::domain::courriel::Courriel;
+use crate::domain::error::MyError;
+
+
+fn construire(expediteur: &Mailbox, courriel: &Courriel) -> Result<Message, MyError> {
+    let destinataire: Mailbox = courriel
+        .destinataire
+        .parse()
+        .map_err(|e| MyError::BadRequest(format!("Destinataire invalide: {}", e)))?;
+    Message::builder()
+        .from(expediteur.clone())
+        .to(destinataire)
+        .subject(courriel.sujet.clone())
+        .body(courriel.corps.clone())
+        .map_err(|e| MyError::Custom(format!("Construction du mail impossible: {}", e)))
+}
+
+// Envoi SMTP (relais TLS, ou serveur local sans TLS type MailHog)
+pub struct MailSmtp {
+    transport: SmtpTransport,
+    expediteur: Mailbox,
+}
+
+impl MailSmtp {
+
+    pub fn new(transport: SmtpTransport, expediteur: Mailbox) -> Self {
+        Self { transport, expediteur }
+    }
+}
+
+#[async_trait]
+impl MailPort for MailSmtp {
+    async fn envoyer(&self, courriel: &Courriel) -> Result<(), MyError> {
+        let message = construire(&self.expediteur, courriel)?;
+        let transport = self.transport.clone();
+        // Le transport SMTP de lettre est bloquant
+        tokio::task::spawn_blocking(move || transport.send(&message))
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?
+            .map_err(|e| MyError::Custom(format!("Envoi du mail impossible: {}", e)))?;
+        Ok(())
+    }
+}
+
+// Écrit chaque mail dans un fichier .eml (développement et tests)
+pub struct MailFichier {
+    dossier: PathBuf,
+    expediteur: Mailbox,
+}
+
+impl MailFichier {
+
+    pub fn new(dossier: PathBuf, expediteur: Mailbox) -> Self {
+        Self { dossier, expediteur }
+    }
+}
+
+#[async_trait]
+impl MailPort for MailFichier {
+    async fn envoyer(&self, courriel: &Courriel) -> Result<(), MyError> {
+        let message = construire(&self.expediteur, courriel)?;
+        tokio::fs::create_dir_all(&self.dossier)
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?;
+        let chemin = self
+            .dossier
+            .join(format!("{}-{}.eml", Utc::now().format("%Y%m%dT%H%M%S"), Uuid::new_v4()));
+        tokio::fs::write(&chemin, message.formatted())
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?;
+        tracing::debug!("Mail écrit dans {}", chemin.display());
+        Ok(())
+    }
+}
+
+// MAIL_TRANSPORT=smtp|fichier (fichier par défaut)
+pub fn depuis_env() -> Result<Arc<dyn MailPort>, MyError> {
+    let expediteur: Mailbox = env::var("MAIL_EXPEDITEUR")
+        .unwrap_or_else(|_| "Boutique <no-reply@localhost>".to_string())
+        .parse()
+        .map_err(|e| MyError::Custom(format!("MAIL_EXPEDITEUR invalide: {}", e)))?;
+
+    match env::var("MAIL_TRANSPORT").as_deref() {
+        Ok("smtp") => {
+            let hote = env::var("SMTP_HOTE").unwrap_or_else(|_| "localhost".to_string());
+            let tls = env::var("SMTP_TLS").map(|v| v == "true").unwrap_or(false);
+            let mut builder = if tls {
+                SmtpTransport::relay(&hote).map_err(|e| MyError::Custom(e.to_string()))?
+            } else {
+                SmtpTransport::builder_dangerous(hote)
+            };
+            if let Some(port) = env::var("SMTP_PORT").ok().and_then(|p| p.parse().ok()) {
+                builder = builder.port(port);
+            }
+            if let (Ok(utilisateur), Ok(mot_de_passe)) = (env::var("SMTP_UTILISATEUR"), env::var("SMTP_MOT_DE_PASSE")) {
+                builder = builder.credentials(Credentials::new(utilisateur, mot_de_passe));
+            }
+            Ok(Arc::new(MailSmtp::new(builder.build(), expediteur)))
+        }
+        _ => {
+            let dossier = env::var("MAIL_DOSSIER").unwrap_or_else(|_| "courriels".to_string());
+            Ok(Arc::new(MailFichier::new(PathBuf::from(dossier), expediteur)))
+        }
+    }
+}
@@ -1 +1,15 @@
-pub mod users;
\ No newline at end of file
+pub mod users;
+pub mod sessions;
+pub mod jetons;
+pub mod mail;
+pub mod tentatives;
+pub mod double_facteur;
+pub mod donnees_personnelles;
+pub mod audit;
+pub mod cles_api;
+pub mod identites_externes;
+pub mod oidc;
+pub mod sms;
+pub mod telephones;
+pub mod produits;
+pub mod categories;
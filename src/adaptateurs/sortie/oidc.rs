@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::ports::oidc::FournisseurOidcPort;
+use crate::domain::oidc::{ConfigFournisseur, ConfigOidc, DemandeOidc, ProfilOidc};
+use crate::domain::error::MyError;
+
+// Signatures asymétriques uniquement : ni "none", ni HS* (le secret client n'est pas une clé de signature)
+const ALGORITHMES: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+// Extrait de {emetteur}/.well-known/openid-configuration
+#[derive(Debug, Deserialize)]
+struct Metadonnees {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReponseJeton {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimsIdToken {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    // Booléen, ou chaîne "true" chez certains fournisseurs
+    email_verified: Option<Value>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+}
+
+
+// Client des fournisseurs configurés ; la découverte et les clés sont relues à chaque connexion,
+// ce qui suit sans délai les rotations de clés
+pub struct ClientOidc {
+    http: reqwest::Client,
+    config: ConfigOidc,
+}
+
+impl ClientOidc {
+
+    pub fn new(config: ConfigOidc) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { http, config }
+    }
+
+    fn fournisseur(&self, nom: &str) -> Result<&ConfigFournisseur, MyError> {
+        self.config
+            .fournisseur(nom)
+            .ok_or_else(|| MyError::NotFound(format!("Fournisseur d'identité inconnu: {}", nom)))
+    }
+
+    async fn lire<T: DeserializeOwned>(&self, reponse: reqwest::RequestBuilder, quoi: &str) -> Result<T, MyError> {
+        let indisponible = |e: reqwest::Error| MyError::FournisseurIndisponible(format!("{}: {}", quoi, e));
+        reponse
+            .send()
+            .await
+            .map_err(indisponible)?
+            .error_for_status()
+            .map_err(indisponible)?
+            .json()
+            .await
+            .map_err(indisponible)
+    }
+
+    async fn metadonnees(&self, fournisseur: &ConfigFournisseur) -> Result<Metadonnees, MyError> {
+        let url = format!("{}/.well-known/openid-configuration", fournisseur.emetteur);
+        let metadonnees: Metadonnees = self.lire(self.http.get(url), "découverte").await?;
+        // L'émetteur annoncé doit être celui qui est configuré (OpenID Connect Discovery, §4.3)
+        if metadonnees.issuer.trim_end_matches('/') != fournisseur.emetteur {
+            return Err(MyError::FournisseurIndisponible("Émetteur annoncé différent de l'émetteur configuré".to_string()));
+        }
+        Ok(metadonnees)
+    }
+
+    async fn valider_id_token(
+        &self,
+        fournisseur: &ConfigFournisseur,
+        metadonnees: &Metadonnees,
+        id_token: &str,
+    ) -> Result<ClaimsIdToken, MyError> {
+        let invalide = || MyError::Unauthorized("id_token invalide".to_string());
+        let entete = decode_header(id_token).map_err(|_| invalide())?;
+        if !ALGORITHMES.contains(&entete.alg) {
+            return Err(invalide());
+        }
+
+        let cles: JwkSet = self.lire(self.http.get(&metadonnees.jwks_uri), "JWKS").await?;
+        let jwk = match &entete.kid {
+            Some(kid) => cles.find(kid),
+            // Sans kid, seule une clé unique est sans ambiguïté
+            None if cles.keys.len() == 1 => cles.keys.first(),
+            None => None,
+        }
+        .ok_or_else(invalide)?;
+        let cle = DecodingKey::from_jwk(jwk).map_err(|_| invalide())?;
+
+        let mut validation = Validation::new(entete.alg);
+        validation.set_audience(&[&fournisseur.client_id]);
+        validation.set_issuer(&[&metadonnees.issuer]);
+        decode::<ClaimsIdToken>(id_token, &cle, &validation)
+            .map(|donnees| donnees.claims)
+            .map_err(|_| invalide())
+    }
+}
+
+
+
+#[async_trait]
+impl FournisseurOidcPort for ClientOidc {
+    async fn url_autorisation(&self, demande: &DemandeOidc, etat: &str) -> Result<String, MyError> {
+        let fournisseur = self.fournisseur(&demande.fournisseur)?;
+        let metadonnees = self.metadonnees(fournisseur).await?;
+        let defi = demande.defi_pkce();
+        let url = reqwest::Url::parse_with_params(
+            &metadonnees.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", fournisseur.client_id.as_str()),
+                ("redirect_uri", fournisseur.redirection.as_str()),
+                ("scope", fournisseur.portees.as_str()),
+                ("state", etat),
+                ("nonce", demande.nonce.as_str()),
+                ("code_challenge", defi.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| MyError::FournisseurIndisponible(format!("authorization_endpoint invalide: {}", e)))?;
+        Ok(url.to_string())
+    }
+
+    async fn authentifier(&self, demande: &DemandeOidc, code: &str) -> Result<ProfilOidc, MyError> {
+        let fournisseur = self.fournisseur(&demande.fournisseur)?;
+        let metadonnees = self.metadonnees(fournisseur).await?;
+
+        let mut formulaire = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", fournisseur.redirection.as_str()),
+            ("client_id", fournisseur.client_id.as_str()),
+            ("code_verifier", demande.verificateur_pkce.as_str()),
+        ];
+        if let Some(secret) = &fournisseur.client_secret {
+            formulaire.push(("client_secret", secret.as_str()));
+        }
+        let reponse: ReponseJeton = self
+            .lire(self.http.post(&metadonnees.token_endpoint).form(&formulaire), "échange du code")
+            .await?;
+
+        let claims = self.valider_id_token(fournisseur, &metadonnees, &reponse.id_token).await?;
+        // Le nonce lie l'id_token à la demande de ce navigateur (rejeu d'un jeton volé)
+        if claims.nonce.as_deref() != Some(demande.nonce.as_str()) {
+            return Err(MyError::Unauthorized("id_token invalide".to_string()));
+        }
+        let email_verifie = matches!(&claims.email_verified, Some(Value::Bool(true)))
+            || matches!(&claims.email_verified, Some(Value::String(valeur)) if valeur == "true");
+
+        Ok(ProfilOidc {
+            sujet: claims.sub,
+            email: claims.email,
+            email_verifie,
+            prenom: claims.given_name,
+            nom: claims.family_name,
+        })
+    }
+}
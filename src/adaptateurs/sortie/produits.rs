@@ -0,0 +1,240 @@
+use async_trait::async_trait;
+use chrono::SecondsFormat;
+use sqlx::{PgPool, Postgres, QueryBuilder, Error as SqlxError};
+use uuid::Uuid;
+
+use crate::ports::produits::ProduitPort;
+use crate::domain::models::Produit;
+use crate::domain::produit::{RequeteProduits, TriProduits};
+use crate::domain::pagination::{limite_effective, motif_recherche, Curseur, Ordre, Page};
+use crate::domain::error::MyError;
+
+const COLONNES: &str =
+    "id, nom, description, reference, prix, quantite, categorie_id, image_principale_url, est_publie, date_creation";
+
+
+pub struct PostgreSqlProduits {
+    pool: PgPool,
+}
+
+impl PostgreSqlProduits {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// Référence en double ou catégorie inexistante
+fn erreur_ecriture(e: SqlxError) -> MyError {
+    match e {
+        SqlxError::Database(db_err) if db_err.constraint() == Some("products_categorie_id_fkey") => {
+            MyError::BadRequest("Catégorie inexistante".to_string())
+        }
+        SqlxError::Database(db_err) if db_err.constraint() == Some("products_reference_key") => {
+            MyError::BadRequest("Référence déjà utilisée".to_string())
+        }
+        // Normalement écartés par la validation, ces CHECK ne doivent pas passer pour un doublon
+        SqlxError::Database(db_err) if db_err.constraint() == Some("products_prix_check") => {
+            MyError::BadRequest("Le prix doit être strictement positif".to_string())
+        }
+        SqlxError::Database(db_err) if db_err.constraint() == Some("products_quantite_check") => {
+            MyError::BadRequest("Quantité négative".to_string())
+        }
+        _ => MyError::Database(e.to_string()),
+    }
+}
+
+fn appliquer_filtres(qb: &mut QueryBuilder<'_, Postgres>, requete: &RequeteProduits) {
+    qb.push(" WHERE TRUE");
+    if let Some(publie) = requete.publie {
+        qb.push(" AND est_publie = ").push_bind(publie);
+    }
+    match (requete.categorie_id, requete.sous_categories) {
+        (Some(categorie_id), true) => {
+            qb.push(
+                " AND categorie_id IN (
+                    WITH RECURSIVE descendantes AS (
+                        SELECT id FROM categories WHERE id = ",
+            )
+            .push_bind(categorie_id)
+            .push(
+                "
+                        UNION ALL
+                        SELECT c.id FROM categories c JOIN descendantes d ON c.parent_id = d.id
+                    )
+                    SELECT id FROM descendantes
+                )",
+            );
+        }
+        (Some(categorie_id), false) => {
+            qb.push(" AND categorie_id = ").push_bind(categorie_id);
+        }
+        (None, _) => {}
+    }
+    if let Some(prix_min) = requete.prix_min {
+        qb.push(" AND prix >= ").push_bind(prix_min);
+    }
+    if let Some(prix_max) = requete.prix_max {
+        qb.push(" AND prix <= ").push_bind(prix_max);
+    }
+    if let Some(recherche) = requete.recherche.as_deref().filter(|r| !r.trim().is_empty()) {
+        let motif = motif_recherche(recherche);
+        qb.push(" AND (nom ILIKE ").push_bind(motif.clone())
+            .push(" OR reference ILIKE ").push_bind(motif)
+            .push(")");
+    }
+}
+
+fn valeur_de_tri(produit: &Produit, tri: TriProduits) -> String {
+    match tri {
+        TriProduits::DateCreation => produit.date_creation.to_rfc3339_opts(SecondsFormat::Micros, true),
+        TriProduits::Nom => produit.nom.clone(),
+        TriProduits::Prix => produit.prix.valeur().to_string(),
+    }
+}
+
+
+#[async_trait]
+impl ProduitPort for PostgreSqlProduits {
+    async fn creer(&self, produit: &Produit) -> Result<Produit, MyError> {
+        let produit = sqlx::query_as::<_, Produit>(&format!(
+            r#"
+            INSERT INTO products (id, nom, description, reference, prix, quantite, categorie_id, image_principale_url, est_publie, date_creation)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {}
+            "#,
+            COLONNES
+        ))
+        .bind(produit.id)
+        .bind(&produit.nom)
+        .bind(&produit.description)
+        .bind(&produit.reference)
+        .bind(produit.prix)
+        .bind(produit.quantite)
+        .bind(produit.categorie_id)
+        .bind(&produit.image_principale_url)
+        .bind(produit.est_publie)
+        .bind(produit.date_creation)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(erreur_ecriture)?;
+
+        Ok(produit)
+    }
+
+    async fn obtenir(&self, id: Uuid) -> Result<Option<Produit>, MyError> {
+        let produit = sqlx::query_as::<_, Produit>(&format!("SELECT {} FROM products WHERE id = $1", COLONNES))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(produit)
+    }
+
+    async fn lister(&self, requete: &RequeteProduits) -> Result<Page<Produit>, MyError> {
+        let limite = limite_effective(requete.limite);
+        let colonne = requete.tri.colonne();
+        let ordre = requete.ordre.sql();
+
+        let mut compte = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM products");
+        appliquer_filtres(&mut compte, requete);
+        let (total,): (i64,) = compte
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM products", COLONNES));
+        appliquer_filtres(&mut qb, requete);
+        if let Some(curseur) = &requete.curseur {
+            // Pagination par clé : on reprend strictement après le dernier élément vu
+            let curseur = Curseur::decoder(curseur)?;
+            let type_sql = match requete.tri {
+                TriProduits::DateCreation => "TIMESTAMPTZ",
+                TriProduits::Nom => "TEXT",
+                TriProduits::Prix => "NUMERIC",
+            };
+            let comparaison = match requete.ordre {
+                Ordre::Asc => ">",
+                Ordre::Desc => "<",
+            };
+            qb.push(format!(" AND ({}, id) {} (CAST(", colonne, comparaison))
+                .push_bind(curseur.valeur)
+                .push(format!(" AS {}), ", type_sql))
+                .push_bind(curseur.id)
+                .push(")");
+        }
+        qb.push(format!(" ORDER BY {} {}, id {}", colonne, ordre, ordre));
+        // Un élément de plus pour savoir s'il existe une page suivante
+        qb.push(" LIMIT ").push_bind(i64::from(limite) + 1);
+        if let (None, Some(page)) = (&requete.curseur, requete.page) {
+            qb.push(" OFFSET ").push_bind(i64::from(page.saturating_sub(1)) * i64::from(limite));
+        }
+
+        let mut produits = qb
+            .build_query_as::<Produit>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let curseur_suivant = if produits.len() > limite as usize {
+            produits.truncate(limite as usize);
+            produits.last().map(|dernier| Curseur {
+                valeur: valeur_de_tri(dernier, requete.tri),
+                id: dernier.id,
+            }.encoder())
+        } else {
+            None
+        };
+
+        Ok(Page { elements: produits, total, limite, curseur_suivant })
+    }
+
+    async fn mettre_a_jour(&self, produit: &Produit) -> Result<Produit, MyError> {
+        let produit = sqlx::query_as::<_, Produit>(&format!(
+            r#"
+            UPDATE products
+            SET nom = $2, description = $3, reference = $4, prix = $5, quantite = $6,
+                categorie_id = $7, image_principale_url = $8, est_publie = $9
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            COLONNES
+        ))
+        .bind(produit.id)
+        .bind(&produit.nom)
+        .bind(&produit.description)
+        .bind(&produit.reference)
+        .bind(produit.prix)
+        .bind(produit.quantite)
+        .bind(produit.categorie_id)
+        .bind(&produit.image_principale_url)
+        .bind(produit.est_publie)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(erreur_ecriture)?;
+
+        produit.ok_or_else(|| MyError::NotFound("Produit non trouvé".to_string()))
+    }
+
+    async fn supprimer(&self, id: Uuid) -> Result<(), MyError> {
+        let result = sqlx::query("DELETE FROM products WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                // Lignes de panier ou de commande (order_items, cart_items) sans ON DELETE CASCADE
+                SqlxError::Database(db_err) if db_err.constraint().is_some() => MyError::BadRequest(
+                    "Produit présent dans des paniers ou des commandes : le dépublier plutôt que le supprimer".to_string(),
+                ),
+                _ => MyError::Database(e.to_string()),
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(MyError::NotFound("Produit non trouvé".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Error as SqlxError};
+use uuid::Uuid;
+
+use crate::ports::sessions::SessionPort;
+use crate::domain::models::Session;
+use crate::domain::session::{JetonRafraichissement, Rotation};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlSession {
+    pool: PgPool,
+}
+
+impl PostgreSqlSession {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// Tâche de fond qui supprime périodiquement les sessions expirées
+pub fn lancer_balayeur(sessions: Arc<dyn SessionPort>, intervalle: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut minuterie = actix_web::rt::time::interval(intervalle);
+        loop {
+            minuterie.tick().await;
+            match sessions.purger_expirees().await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("{} session(s) expirée(s) purgée(s)", n),
+                Err(e) => tracing::error!("Purge des sessions impossible: {}", e),
+            }
+        }
+    });
+}
+
+
+
+#[async_trait]
+impl SessionPort for PostgreSqlSession {
+    async fn creer(&self, session: &Session) -> Result<Session, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            INSERT INTO sessions (id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+            "#,
+        )
+        .bind(session.id)
+        .bind(&session.token)
+        .bind(session.utilisateur_id)
+        .bind(session.date_expiration)
+        .bind(session.date_creation)
+        .bind(&session.appareil)
+        .bind(&session.adresse_ip)
+        .bind(session.derniere_activite)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session)
+    }
+
+    async fn creer_usurpation(&self, session: &Session, parente_id: Option<Uuid>) -> Result<Session, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            INSERT INTO sessions (id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite, session_parente_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+            "#,
+        )
+        .bind(session.id)
+        .bind(&session.token)
+        .bind(session.utilisateur_id)
+        .bind(session.date_expiration)
+        .bind(session.date_creation)
+        .bind(&session.appareil)
+        .bind(&session.adresse_ip)
+        .bind(session.derniere_activite)
+        .bind(parente_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session)
+    }
+
+    async fn obtenir_par_token(&self, token: &str) -> Result<Option<Session>, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            "SELECT id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+             FROM sessions WHERE token = $1 AND date_expiration > NOW()"
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session)
+    }
+
+    async fn prolonger(&self, token: &str, date_expiration: DateTime<Utc>) -> Result<Session, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions
+            SET date_expiration = $2
+            WHERE token = $1 AND date_expiration > NOW()
+            RETURNING id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+            "#,
+        )
+        .bind(token)
+        .bind(date_expiration)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::RowNotFound => MyError::NotFound("Session non trouvée".to_string()),
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        Ok(session)
+    }
+
+    async fn rattacher(&self, token: &str, utilisateur_id: Uuid) -> Result<Session, MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions
+            SET utilisateur_id = $2
+            WHERE token = $1 AND date_expiration > NOW()
+            RETURNING id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+            "#,
+        )
+        .bind(token)
+        .bind(utilisateur_id)
+        .fetch_one(&mut tx)
+        .await
+        .map_err(|e| match e {
+            SqlxError::RowNotFound => MyError::NotFound("Session non trouvée".to_string()),
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        // Le panier invité suit l'utilisateur qui se connecte
+        sqlx::query("UPDATE cart_items SET utilisateur_id = $2 WHERE session_id = $1 AND utilisateur_id IS NULL")
+            .bind(session.id)
+            .bind(utilisateur_id)
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session)
+    }
+
+    async fn revoquer(&self, token: &str) -> Result<(), MyError> {
+        // On expire la session plutôt que de la supprimer : cart_items la référence
+        // Les usurpations ouvertes depuis la session prennent fin avec elle
+        let result = sqlx::query(
+            "UPDATE sessions SET date_expiration = NOW()
+             WHERE (token = $1 OR session_parente_id = (SELECT id FROM sessions WHERE token = $1))
+             AND date_expiration > NOW()"
+        )
+        .bind(token)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(MyError::NotFound("Session non trouvée".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn lister_actives(&self, utilisateur_id: Uuid) -> Result<Vec<Session>, MyError> {
+        let sessions = sqlx::query_as::<_, Session>(
+            "SELECT id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+             FROM sessions WHERE utilisateur_id = $1 AND date_expiration > NOW()
+             ORDER BY derniere_activite DESC"
+        )
+        .bind(utilisateur_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(sessions)
+    }
+
+    async fn obtenir_par_id(&self, id: Uuid) -> Result<Option<Session>, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            "SELECT id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+             FROM sessions WHERE id = $1 AND date_expiration > NOW()"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session)
+    }
+
+    async fn revoquer_par_id(&self, id: Uuid) -> Result<(), MyError> {
+        let result = sqlx::query(
+            "UPDATE sessions SET date_expiration = NOW()
+             WHERE (id = $1 OR session_parente_id = $1) AND date_expiration > NOW()"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(MyError::NotFound("Session non trouvée".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn revoquer_tout(&self, utilisateur_id: Uuid) -> Result<u64, MyError> {
+        let result = sqlx::query(
+            "UPDATE sessions SET date_expiration = NOW() WHERE utilisateur_id = $1 AND date_expiration > NOW()"
+        )
+        .bind(utilisateur_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn ajouter_rafraichissement(&self, jeton: &JetonRafraichissement) -> Result<(), MyError> {
+        sqlx::query(
+            r#"
+            INSERT INTO jetons_rafraichissement (id, session_id, jeton_hache, date_utilisation, date_creation)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(jeton.id)
+        .bind(jeton.session_id)
+        .bind(&jeton.jeton_hache)
+        .bind(jeton.date_utilisation)
+        .bind(jeton.date_creation)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn consommer_rafraichissement(&self, jeton_hache: &str) -> Result<Rotation, MyError> {
+        // UPDATE conditionnel : deux requêtes concurrentes ne peuvent pas consommer le même jeton
+        let consomme: Option<(Uuid,)> = sqlx::query_as(
+            "UPDATE jetons_rafraichissement SET date_utilisation = NOW()
+             WHERE jeton_hache = $1 AND date_utilisation IS NULL
+             RETURNING session_id"
+        )
+        .bind(jeton_hache)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        if let Some((session_id,)) = consomme {
+            return Ok(match self.obtenir_par_id(session_id).await? {
+                Some(session) => Rotation::Valide(session),
+                None => Rotation::Inconnu,
+            });
+        }
+
+        // Jeton déjà échangé : on retrouve sa session, même expirée
+        let session = sqlx::query_as::<_, Session>(
+            "SELECT s.id, s.token, s.utilisateur_id, s.date_expiration, s.date_creation, s.appareil, s.adresse_ip, s.derniere_activite
+             FROM jetons_rafraichissement j JOIN sessions s ON s.id = j.session_id
+             WHERE j.jeton_hache = $1"
+        )
+        .bind(jeton_hache)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(session.map_or(Rotation::Inconnu, Rotation::Reutilise))
+    }
+
+    async fn toucher(&self, id: Uuid, adresse_ip: Option<String>, date_expiration: DateTime<Utc>) -> Result<Session, MyError> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            UPDATE sessions
+            SET derniere_activite = NOW(), adresse_ip = COALESCE($2, adresse_ip), date_expiration = $3
+            WHERE id = $1 AND date_expiration > NOW()
+            RETURNING id, token, utilisateur_id, date_expiration, date_creation, appareil, adresse_ip, derniere_activite
+            "#,
+        )
+        .bind(id)
+        .bind(adresse_ip)
+        .bind(date_expiration)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::RowNotFound => MyError::NotFound("Session non trouvée".to_string()),
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        Ok(session)
+    }
+
+    async fn purger_expirees(&self) -> Result<u64, MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        // Paniers invités orphelins : supprimés ; paniers d'un compte : détachés de la session
+        sqlx::query(
+            "DELETE FROM cart_items
+             WHERE utilisateur_id IS NULL
+               AND session_id IN (SELECT id FROM sessions WHERE date_expiration <= NOW())"
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query(
+            "UPDATE cart_items SET session_id = NULL
+             WHERE session_id IN (SELECT id FROM sessions WHERE date_expiration <= NOW())"
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let result = sqlx::query("DELETE FROM sessions WHERE date_expiration <= NOW()")
+            .execute(&mut tx)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,54 @@
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use tokio::io::AsyncWriteExt;
+
+use crate::ports::sms::SmsPort;
+use crate::domain::sms::Sms;
+use crate::domain::error::MyError;
+
+
+// Ajoute chaque SMS à un fichier texte et aux logs, au lieu de l'envoyer (développement et tests)
+pub struct SmsJournal {
+    fichier: PathBuf,
+}
+
+impl SmsJournal {
+
+    pub fn new(fichier: PathBuf) -> Self {
+        Self { fichier }
+    }
+}
+
+#[async_trait]
+impl SmsPort for SmsJournal {
+    async fn envoyer(&self, sms: &Sms) -> Result<(), MyError> {
+        let ligne = format!("{}\t{}\t{}\n", Utc::now().to_rfc3339(), sms.destinataire, sms.texte);
+        let mut fichier = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.fichier)
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?;
+        fichier
+            .write_all(ligne.as_bytes())
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?;
+        tracing::debug!("SMS pour {} écrit dans {}", sms.destinataire, self.fichier.display());
+        Ok(())
+    }
+}
+
+// SMS_TRANSPORT=journal (seul transport pour l'instant), fichier SMS_FICHIER (sms.log par défaut)
+pub fn depuis_env() -> Result<Arc<dyn SmsPort>, MyError> {
+    match env::var("SMS_TRANSPORT").as_deref() {
+        Ok("journal") | Err(_) => {
+            let fichier = env::var("SMS_FICHIER").unwrap_or_else(|_| "sms.log".to_string());
+            Ok(Arc::new(SmsJournal::new(PathBuf::from(fichier))))
+        }
+        Ok(autre) => Err(MyError::Custom(format!("SMS_TRANSPORT inconnu: {}", autre))),
+    }
+}
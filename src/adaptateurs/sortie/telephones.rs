@@ -0,0 +1,168 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Error as SqlxError};
+use uuid::Uuid;
+
+use crate::ports::telephones::TelephonePort;
+use crate::domain::sms::{CodeSms, ObjetCodeSms, Telephone, ESSAIS_MAX_CODE_SMS};
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlTelephones {
+    pool: PgPool,
+}
+
+impl PostgreSqlTelephones {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl TelephonePort for PostgreSqlTelephones {
+    async fn obtenir(&self, utilisateur_id: Uuid) -> Result<Option<Telephone>, MyError> {
+        let telephone = sqlx::query_as::<_, Telephone>(
+            "SELECT utilisateur_id, numero, date_verification, date_creation FROM telephones WHERE utilisateur_id = $1"
+        )
+        .bind(utilisateur_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(telephone)
+    }
+
+    async fn obtenir_verifie(&self, numero: &str) -> Result<Option<Telephone>, MyError> {
+        let telephone = sqlx::query_as::<_, Telephone>(
+            "SELECT t.utilisateur_id, t.numero, t.date_verification, t.date_creation
+             FROM telephones t JOIN utilisateur u ON u.id = t.utilisateur_id
+             WHERE t.numero = $1 AND t.date_verification IS NOT NULL AND u.date_suppression IS NULL"
+        )
+        .bind(numero)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(telephone)
+    }
+
+    async fn enregistrer(&self, utilisateur_id: Uuid, numero: &str) -> Result<Telephone, MyError> {
+        // Le même numéro déjà vérifié le reste
+        let telephone = sqlx::query_as::<_, Telephone>(
+            r#"
+            INSERT INTO telephones (utilisateur_id, numero)
+            VALUES ($1, $2)
+            ON CONFLICT (utilisateur_id) DO UPDATE
+            SET numero = EXCLUDED.numero,
+                date_verification = CASE WHEN telephones.numero = EXCLUDED.numero THEN telephones.date_verification END,
+                date_creation = CASE WHEN telephones.numero = EXCLUDED.numero THEN telephones.date_creation ELSE NOW() END
+            RETURNING utilisateur_id, numero, date_verification, date_creation
+            "#,
+        )
+        .bind(utilisateur_id)
+        .bind(numero)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(telephone)
+    }
+
+    async fn marquer_verifie(&self, utilisateur_id: Uuid, numero: &str) -> Result<Telephone, MyError> {
+        sqlx::query_as::<_, Telephone>(
+            "UPDATE telephones SET date_verification = COALESCE(date_verification, NOW())
+             WHERE utilisateur_id = $1 AND numero = $2
+             RETURNING utilisateur_id, numero, date_verification, date_creation"
+        )
+        .bind(utilisateur_id)
+        .bind(numero)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::Database(db_err) if db_err.constraint().is_some() => {
+                MyError::BadRequest("Numéro déjà utilisé par un autre compte".to_string())
+            }
+            _ => MyError::Database(e.to_string()),
+        })?
+        .ok_or_else(|| MyError::BadRequest("Aucun numéro en attente de vérification".to_string()))
+    }
+
+    async fn creer_code(&self, code: &CodeSms) -> Result<(), MyError> {
+        let mut tx = self.pool.begin().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query(
+            "UPDATE codes_sms SET date_utilisation = NOW()
+             WHERE utilisateur_id = $1 AND objet = $2 AND date_utilisation IS NULL"
+        )
+        .bind(code.utilisateur_id)
+        .bind(code.objet)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO codes_sms (id, utilisateur_id, objet, telephone, code_hache, essais, date_expiration, date_creation)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        )
+        .bind(code.id)
+        .bind(code.utilisateur_id)
+        .bind(code.objet)
+        .bind(&code.telephone)
+        .bind(&code.code_hache)
+        .bind(code.essais)
+        .bind(code.date_expiration)
+        .bind(code.date_creation)
+        .execute(&mut tx)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn consommer_code(
+        &self,
+        utilisateur_id: Uuid,
+        objet: ObjetCodeSms,
+        telephone: &str,
+        code_hache: &str,
+    ) -> Result<bool, MyError> {
+        let consomme = sqlx::query(
+            "UPDATE codes_sms SET date_utilisation = NOW()
+             WHERE utilisateur_id = $1 AND objet = $2 AND telephone = $3 AND code_hache = $4
+             AND date_utilisation IS NULL AND date_expiration > NOW() AND essais < $5"
+        )
+        .bind(utilisateur_id)
+        .bind(objet)
+        .bind(telephone)
+        .bind(code_hache)
+        .bind(ESSAIS_MAX_CODE_SMS)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?
+        .rows_affected() > 0;
+
+        if !consomme {
+            // Mauvais code : un essai de plus sur le code en cours, invalidé au dernier
+            sqlx::query(
+                "UPDATE codes_sms
+                 SET essais = essais + 1,
+                     date_utilisation = CASE WHEN essais + 1 >= $4 THEN NOW() END
+                 WHERE utilisateur_id = $1 AND objet = $2 AND telephone = $3
+                 AND date_utilisation IS NULL AND date_expiration > NOW()"
+            )
+            .bind(utilisateur_id)
+            .bind(objet)
+            .bind(telephone)
+            .bind(ESSAIS_MAX_CODE_SMS)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+        }
+
+        Ok(consomme)
+    }
+}
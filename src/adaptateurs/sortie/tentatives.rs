@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use crate::ports::tentatives::TentativesPort;
+use crate::domain::tentatives::EtatTentatives;
+use crate::domain::error::MyError;
+
+
+pub struct PostgreSqlTentatives {
+    pool: PgPool,
+}
+
+impl PostgreSqlTentatives {
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+
+
+#[async_trait]
+impl TentativesPort for PostgreSqlTentatives {
+    async fn obtenir(&self, cle: &str) -> Result<Option<EtatTentatives>, MyError> {
+        let etat = sqlx::query_as::<_, EtatTentatives>(
+            "SELECT cle, echecs, bloque_jusqu_a, derniere_tentative FROM tentatives_connexion WHERE cle = $1"
+        )
+        .bind(cle)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(etat)
+    }
+
+    async fn incrementer(&self, cle: &str, fenetre_oubli: Duration) -> Result<i32, MyError> {
+        let echecs = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO tentatives_connexion (cle, echecs, derniere_tentative)
+            VALUES ($1, 1, NOW())
+            ON CONFLICT (cle) DO UPDATE
+            SET echecs = CASE
+                    WHEN tentatives_connexion.derniere_tentative < NOW() - make_interval(secs => $2) THEN 1
+                    ELSE tentatives_connexion.echecs + 1
+                END,
+                derniere_tentative = NOW()
+            RETURNING echecs
+            "#,
+        )
+        .bind(cle)
+        .bind(fenetre_oubli.num_seconds() as f64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(echecs)
+    }
+
+    async fn bloquer(&self, cle: &str, jusqu_a: DateTime<Utc>) -> Result<(), MyError> {
+        sqlx::query("UPDATE tentatives_connexion SET bloque_jusqu_a = $2 WHERE cle = $1")
+            .bind(cle)
+            .bind(jusqu_a)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn effacer(&self, cle: &str) -> Result<(), MyError> {
+        sqlx::query("DELETE FROM tentatives_connexion WHERE cle = $1")
+            .bind(cle)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
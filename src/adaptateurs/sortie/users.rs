@@ -1,20 +1,64 @@
 use async_trait::async_trait;
-use sqlx::{PgPool, Error as SqlxError};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder, Error as SqlxError};
 use uuid::Uuid;
 
 use crate::ports::users::UtilisateurEntree;
-use crate::domain::user::{Utilisateur, CreateUser, UpdateUser};
+use crate::domain::user::{RequeteUtilisateurs, TriUtilisateurs, Utilisateur};
+use crate::domain::pagination::{limite_effective, motif_recherche, Curseur, Ordre, Page};
 use crate::domain::error::MyError;
+use crate::domain::role::Role;
+use crate::domain::concurrence::Precondition;
+use crate::domain::email::ReglesEmail;
 
 
 pub struct PostgreSql {
     pool: PgPool,
+    // Calcul de email_cle, la clé d'unicité et de recherche
+    regles: ReglesEmail,
 }
 
 impl PostgreSql {
 
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, regles: ReglesEmail) -> Self {
+        Self { pool, regles }
+    }
+
+    // Aucune ligne écrite : utilisateur absent, ou version différente de celle attendue
+    async fn echec_conditionnel(&self, id: Uuid) -> MyError {
+        match self.obtenir_par_id(id).await {
+            Ok(Some(_)) => MyError::PreconditionEchouee("La ressource a été modifiée depuis sa lecture".to_string()),
+            Ok(None) => MyError::NotFound("Utilisateur non trouvé".to_string()),
+            Err(e) => e,
+        }
+    }
+}
+
+fn appliquer_filtres(qb: &mut QueryBuilder<'_, Postgres>, requete: &RequeteUtilisateurs) {
+    qb.push(" WHERE TRUE");
+    if !requete.inclure_supprimes {
+        qb.push(" AND date_suppression IS NULL");
+    }
+    if let Some(role) = requete.role {
+        qb.push(" AND role = ").push_bind(role);
+    }
+    if let Some(cree_apres) = requete.cree_apres {
+        qb.push(" AND date_creation > ").push_bind(cree_apres);
+    }
+    if let Some(recherche) = requete.recherche.as_deref().filter(|r| !r.trim().is_empty()) {
+        let motif = motif_recherche(recherche);
+        qb.push(" AND (prenom ILIKE ").push_bind(motif.clone())
+            .push(" OR nom ILIKE ").push_bind(motif.clone())
+            .push(" OR email ILIKE ").push_bind(motif)
+            .push(")");
+    }
+}
+
+fn valeur_de_tri(utilisateur: &Utilisateur, tri: TriUtilisateurs) -> String {
+    match tri {
+        TriUtilisateurs::DateCreation => utilisateur.date_creation.to_rfc3339_opts(SecondsFormat::Micros, true),
+        TriUtilisateurs::Nom => utilisateur.nom.clone(),
+        TriUtilisateurs::Email => utilisateur.email.clone(),
     }
 }
 
@@ -25,9 +69,9 @@ impl UtilisateurEntree for PostgreSql {
     async fn creer(&self, utilisateur: &Utilisateur) -> Result<Utilisateur, MyError> {
         let user = sqlx::query_as::<_, Utilisateur>(
             r#"
-            INSERT INTO utilisateur (id, email, mot_de_passe, prenom, nom, role, date_creation, date_update)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, email, mot_de_passe, prenom, nom, role, date_creation, date_update
+            INSERT INTO utilisateur (id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, email_cle)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
             "#,
         )
         .bind(utilisateur.id)
@@ -35,9 +79,11 @@ impl UtilisateurEntree for PostgreSql {
         .bind(&utilisateur.mot_de_passe)
         .bind(&utilisateur.prenom)
         .bind(&utilisateur.nom)
-        .bind(&utilisateur.role)
+        .bind(utilisateur.role)
+        .bind(utilisateur.email_verifie)
         .bind(utilisateur.date_creation)
         .bind(utilisateur.date_update)
+        .bind(self.regles.cle(&utilisateur.email))
         .fetch_one(&self.pool)
         .await
         .map_err(|e| match e {
@@ -52,8 +98,8 @@ impl UtilisateurEntree for PostgreSql {
 
     async fn obtenir_par_id(&self, id: Uuid) -> Result<Option<Utilisateur>, MyError> {
         let user = sqlx::query_as::<_, Utilisateur>(
-            "SELECT id, email, mot_de_passe, prenom, nom, role, date_creation, date_update 
-             FROM utilisateur WHERE id = $1"
+            "SELECT id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+             FROM utilisateur WHERE id = $1 AND date_suppression IS NULL"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -65,8 +111,8 @@ impl UtilisateurEntree for PostgreSql {
 
     async fn obtenir_par_nom(&self, nom: &str) -> Result<Option<Utilisateur>, MyError> {
         let user = sqlx::query_as::<_, Utilisateur>(
-            "SELECT id, email, mot_de_passe, prenom, nom, role, date_creation, date_update 
-             FROM utilisateur WHERE nom = $1"
+            "SELECT id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+             FROM utilisateur WHERE nom = $1 AND date_suppression IS NULL"
         )
         .bind(nom)
         .fetch_optional(&self.pool)
@@ -78,10 +124,10 @@ impl UtilisateurEntree for PostgreSql {
 
     async fn obtenir_par_email(&self, email: &str) -> Result<Option<Utilisateur>, MyError> {
         let user = sqlx::query_as::<_, Utilisateur>(
-            "SELECT id, email, mot_de_passe, prenom, nom, role, date_creation, date_update 
-             FROM utilisateur WHERE email = $1"
+            "SELECT id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+             FROM utilisateur WHERE email_cle = $1 AND date_suppression IS NULL"
         )
-        .bind(email)
+        .bind(self.regles.cle(email))
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| MyError::Database(e.to_string()))?;
@@ -89,10 +135,70 @@ impl UtilisateurEntree for PostgreSql {
         Ok(user)
     }
 
-    async fn obtenir_tous(&self) -> Result<Vec<Utilisateur>, MyError> {
+    async fn lister(&self, requete: &RequeteUtilisateurs) -> Result<Page<Utilisateur>, MyError> {
+        let limite = limite_effective(requete.limite);
+        let colonne = requete.tri.colonne();
+        let ordre = requete.ordre.sql();
+
+        let mut compte = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM utilisateur");
+        appliquer_filtres(&mut compte, requete);
+        let (total,): (i64,) = compte
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression FROM utilisateur"
+        );
+        appliquer_filtres(&mut qb, requete);
+        if let Some(curseur) = &requete.curseur {
+            // Pagination par clé : on reprend strictement après le dernier élément vu
+            let curseur = Curseur::decoder(curseur)?;
+            let type_sql = match requete.tri {
+                TriUtilisateurs::DateCreation => "TIMESTAMPTZ",
+                TriUtilisateurs::Nom | TriUtilisateurs::Email => "TEXT",
+            };
+            let comparaison = match requete.ordre {
+                Ordre::Asc => ">",
+                Ordre::Desc => "<",
+            };
+            qb.push(format!(" AND ({}, id) {} (CAST(", colonne, comparaison))
+                .push_bind(curseur.valeur)
+                .push(format!(" AS {}), ", type_sql))
+                .push_bind(curseur.id)
+                .push(")");
+        }
+        qb.push(format!(" ORDER BY {} {}, id {}", colonne, ordre, ordre));
+        // Un élément de plus pour savoir s'il existe une page suivante
+        qb.push(" LIMIT ").push_bind(i64::from(limite) + 1);
+        if let (None, Some(page)) = (&requete.curseur, requete.page) {
+            qb.push(" OFFSET ").push_bind(i64::from(page.saturating_sub(1)) * i64::from(limite));
+        }
+
+        let mut users = qb
+            .build_query_as::<Utilisateur>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| MyError::Database(e.to_string()))?;
+
+        let curseur_suivant = if users.len() > limite as usize {
+            users.truncate(limite as usize);
+            users.last().map(|dernier| Curseur {
+                valeur: valeur_de_tri(dernier, requete.tri),
+                id: dernier.id,
+            }.encoder())
+        } else {
+            None
+        };
+
+        Ok(Page { elements: users, total, limite, curseur_suivant })
+    }
+
+    async fn lister_tous(&self) -> Result<Vec<Utilisateur>, MyError> {
         let users = sqlx::query_as::<_, Utilisateur>(
-            "SELECT id, email, mot_de_passe, prenom, nom, role, date_creation, date_update 
-             FROM utilisateur"
+            "SELECT id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+             FROM utilisateur ORDER BY date_creation, id"
         )
         .fetch_all(&self.pool)
         .await
@@ -101,13 +207,13 @@ impl UtilisateurEntree for PostgreSql {
         Ok(users)
     }
 
-    async fn mettre_a_jour(&self, utilisateur: &Utilisateur) -> Result<Utilisateur, MyError> {
+    async fn mettre_a_jour(&self, utilisateur: &Utilisateur, precondition: &Precondition) -> Result<Utilisateur, MyError> {
         let user = sqlx::query_as::<_, Utilisateur>(
             r#"
             UPDATE utilisateur
-            SET email = $2, mot_de_passe = $3, prenom = $4, nom = $5, date_update = $6
-            WHERE id = $1
-            RETURNING id, email, mot_de_passe, prenom, nom, role, date_creation, date_update
+            SET email = $2, mot_de_passe = $3, prenom = $4, nom = $5, email_verifie = $6, date_update = $7, email_cle = $9
+            WHERE id = $1 AND date_suppression IS NULL AND ($8::TIMESTAMPTZ[] IS NULL OR date_update = ANY($8))
+            RETURNING id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
             "#,
         )
         .bind(utilisateur.id)
@@ -115,28 +221,115 @@ impl UtilisateurEntree for PostgreSql {
         .bind(&utilisateur.mot_de_passe)
         .bind(&utilisateur.prenom)
         .bind(&utilisateur.nom)
+        .bind(utilisateur.email_verifie)
         .bind(utilisateur.date_update)
-        .fetch_one(&self.pool)
+        .bind(precondition.versions())
+        .bind(self.regles.cle(&utilisateur.email))
+        .fetch_optional(&self.pool)
         .await
         .map_err(|e| match e {
-            SqlxError::RowNotFound => MyError::NotFound("Utilisateur non trouvé".to_string()),
+            SqlxError::Database(db_err) if db_err.constraint().is_some() => {
+                MyError::BadRequest("Email déjà utilisé".to_string())
+            }
             _ => MyError::Database(e.to_string()),
         })?;
 
-        Ok(user)
+        match user {
+            Some(user) => Ok(user),
+            None => Err(self.echec_conditionnel(utilisateur.id).await),
+        }
     }
 
-    async fn supprimer(&self, id: Uuid) -> Result<(), MyError> {
-        let result = sqlx::query("DELETE FROM utilisateur WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| MyError::Database(e.to_string()))?;
+    async fn reecrire_email(&self, id: Uuid, email: &str) -> Result<bool, MyError> {
+        let result = sqlx::query(
+            "UPDATE utilisateur SET email = $2, email_cle = $3, date_update = NOW()
+             WHERE id = $1 AND (email <> $2 OR email_cle IS DISTINCT FROM $3)"
+        )
+        .bind(id)
+        .bind(email)
+        .bind(self.regles.cle(email))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::Database(db_err) if db_err.constraint().is_some() => {
+                MyError::BadRequest("Email déjà utilisé".to_string())
+            }
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn changer_role(&self, id: Uuid, role: Role, precondition: &Precondition) -> Result<Utilisateur, MyError> {
+        let user = sqlx::query_as::<_, Utilisateur>(
+            r#"
+            UPDATE utilisateur
+            SET role = $2, date_update = NOW()
+            WHERE id = $1 AND date_suppression IS NULL AND ($3::TIMESTAMPTZ[] IS NULL OR date_update = ANY($3))
+            RETURNING id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+            "#,
+        )
+        .bind(id)
+        .bind(role)
+        .bind(precondition.versions())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        match user {
+            Some(user) => Ok(user),
+            None => Err(self.echec_conditionnel(id).await),
+        }
+    }
+
+    async fn supprimer(&self, id: Uuid, precondition: &Precondition) -> Result<(), MyError> {
+        let result = sqlx::query(
+            "UPDATE utilisateur SET date_suppression = NOW()
+             WHERE id = $1 AND date_suppression IS NULL AND ($2::TIMESTAMPTZ[] IS NULL OR date_update = ANY($2))"
+        )
+        .bind(id)
+        .bind(precondition.versions())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
 
         if result.rows_affected() == 0 {
-            return Err(MyError::NotFound("Utilisateur non trouvé".to_string()));
+            return Err(self.echec_conditionnel(id).await);
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn restaurer(&self, id: Uuid) -> Result<Utilisateur, MyError> {
+        let user = sqlx::query_as::<_, Utilisateur>(
+            r#"
+            UPDATE utilisateur
+            SET date_suppression = NULL, date_update = NOW()
+            WHERE id = $1 AND date_suppression IS NOT NULL AND date_effacement IS NULL
+            RETURNING id, email, mot_de_passe, prenom, nom, role, email_verifie, date_creation, date_update, date_suppression
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            SqlxError::RowNotFound => MyError::NotFound("Aucun compte supprimé avec cet identifiant".to_string()),
+            _ => MyError::Database(e.to_string()),
+        })?;
+
+        Ok(user)
+    }
+
+    async fn supprimes_avant(&self, limite: DateTime<Utc>) -> Result<Vec<Uuid>, MyError> {
+        let ids: Vec<(Uuid,)> = sqlx::query_as(
+            "SELECT id FROM utilisateur
+             WHERE date_suppression IS NOT NULL AND date_suppression < $1 AND date_effacement IS NULL"
+        )
+        .bind(limite)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| MyError::Database(e.to_string()))?;
+
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+}
@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::domain::user::{ProfilAdmin, Utilisateur};
+
+// Colonne journal_audit.action
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ActionAudit {
+    CreationUtilisateur,
+    ModificationUtilisateur,
+    ChangementRole,
+    DeverrouillageUtilisateur,
+    SuppressionUtilisateur,
+    RestaurationUtilisateur,
+    EffacementUtilisateur,
+    Connexion,
+    Deconnexion,
+    RevocationSession,
+    RevocationSessions,
+    // Jeton de rafraîchissement rejoué : la session a été révoquée
+    ReutilisationRafraichissement,
+    DemandeReinitialisationMotDePasse,
+    ReinitialisationMotDePasse,
+    ConfirmationEmail,
+    DemandeVerificationEmail,
+    InscriptionDoubleFacteur,
+    ActivationDoubleFacteur,
+    DesactivationDoubleFacteur,
+    DebutUsurpation,
+    // Toute requête faite avec un jeton d'usurpation, lectures comprises
+    RequeteUsurpee,
+    CreationCleApi,
+    RevocationCleApi,
+    // Identité OpenID Connect rattachée à un compte
+    LiaisonIdentiteExterne,
+    DemandeLienMagique,
+    ChangementTelephone,
+    VerificationTelephone,
+    CreationProduit,
+    ModificationProduit,
+    SuppressionProduit,
+    CreationCategorie,
+    ModificationCategorie,
+    DeplacementCategorie,
+    SuppressionCategorie,
+}
+
+impl ActionAudit {
+    // Nature de la cible, pour filtrer le journal (les commandes viendront s'ajouter ici)
+    pub fn type_cible(&self) -> &'static str {
+        match self {
+            ActionAudit::Deconnexion
+            | ActionAudit::RevocationSession
+            | ActionAudit::ReutilisationRafraichissement => "session",
+            ActionAudit::CreationCleApi | ActionAudit::RevocationCleApi => "cle_api",
+            ActionAudit::CreationProduit
+            | ActionAudit::ModificationProduit
+            | ActionAudit::SuppressionProduit => "produit",
+            ActionAudit::CreationCategorie
+            | ActionAudit::ModificationCategorie
+            | ActionAudit::DeplacementCategorie
+            | ActionAudit::SuppressionCategorie => "categorie",
+            _ => "utilisateur",
+        }
+    }
+}
+
+// Table: journal_audit (ajout seul, les modifications sont refusées par un trigger)
+#[derive(Debug, Serialize, Clone, FromRow)]
+pub struct EntreeAudit {
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    // None : action anonyme (demande de réinitialisation, ...)
+    pub acteur_id: Option<Uuid>,
+    pub action: ActionAudit,
+    pub type_cible: String,
+    pub cible_id: Option<Uuid>,
+    // Administrateur agissant en tant que l'acteur
+    pub usurpateur_id: Option<Uuid>,
+    // { champ: { avant, apres } }
+    pub changements: Option<Value>,
+    pub requete_id: Option<String>,
+    pub adresse_ip: Option<String>,
+}
+
+impl EntreeAudit {
+    pub fn new(action: ActionAudit, acteur_id: Option<Uuid>, cible_id: Option<Uuid>) -> Self {
+        EntreeAudit {
+            id: Uuid::new_v4(),
+            date: Utc::now(),
+            acteur_id,
+            action,
+            type_cible: action.type_cible().to_string(),
+            cible_id,
+            usurpateur_id: None,
+            changements: None,
+            requete_id: None,
+            adresse_ip: None,
+        }
+    }
+
+    pub fn avec_changements(mut self, changements: Value) -> Self {
+        self.changements = Some(changements);
+        self
+    }
+}
+
+// Champs qui diffèrent entre deux instantanés JSON ; date_update change à chaque écriture et n'apporte rien au journal
+fn differences(avant: &Value, apres: &Value) -> Map<String, Value> {
+    let vide = Map::new();
+    let champs_avant = avant.as_object().unwrap_or(&vide);
+    let champs_apres = apres.as_object().unwrap_or(&vide);
+
+    let mut changements = Map::new();
+    for champ in champs_avant.keys().chain(champs_apres.keys()) {
+        if champ == "date_update" || changements.contains_key(champ) {
+            continue;
+        }
+        let (a, b) = (champs_avant.get(champ), champs_apres.get(champ));
+        if a != b {
+            changements.insert(champ.clone(), json!({ "avant": a, "apres": b }));
+        }
+    }
+    changements
+}
+
+// Le journal est en ajout seul : l'effacement d'un compte ne pourrait pas en retirer les données
+// personnelles, qui n'y figurent donc que masquées (seul le nom du champ modifié reste lisible)
+pub const MASQUE: &str = "***";
+const CHAMPS_PERSONNELS: &[&str] = &["email", "prenom", "nom"];
+
+fn masquer(valeur: &Value) -> Value {
+    match valeur {
+        Value::Null => Value::Null,
+        _ => Value::from(MASQUE),
+    }
+}
+
+// Champs qui diffèrent entre deux états d'un compte ; mot de passe et données personnelles masqués
+pub fn changements_utilisateur(avant: Option<&Utilisateur>, apres: Option<&Utilisateur>) -> Value {
+    let instantane = |utilisateur: Option<&Utilisateur>| match utilisateur {
+        Some(utilisateur) => serde_json::to_value(ProfilAdmin::from(utilisateur)).unwrap_or(Value::Null),
+        None => Value::Null,
+    };
+    let mut changements = differences(&instantane(avant), &instantane(apres));
+    for champ in CHAMPS_PERSONNELS {
+        if let Some(Value::Object(changement)) = changements.get_mut(*champ) {
+            for valeur in changement.values_mut() {
+                *valeur = masquer(valeur);
+            }
+        }
+    }
+    if let (Some(avant), Some(apres)) = (avant, apres)
+        && avant.mot_de_passe != apres.mot_de_passe
+    {
+        changements.insert("mot_de_passe".to_string(), json!({ "avant": MASQUE, "apres": MASQUE }));
+    }
+    Value::Object(changements)
+}
+
+// Entités du catalogue (produits, catégories) : tous leurs champs sont publics
+pub fn changements<T: Serialize>(avant: Option<&T>, apres: Option<&T>) -> Value {
+    let instantane = |entite: Option<&T>| serde_json::to_value(entite).unwrap_or(Value::Null);
+    Value::Object(differences(&instantane(avant), &instantane(apres)))
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RequeteAudit {
+    pub acteur_id: Option<Uuid>,
+    pub cible_id: Option<Uuid>,
+    pub usurpateur_id: Option<Uuid>,
+    pub action: Option<ActionAudit>,
+    pub type_cible: Option<String>,
+    pub requete_id: Option<String>,
+    pub depuis: Option<DateTime<Utc>>,
+    pub jusqu_a: Option<DateTime<Utc>>,
+    pub limite: Option<u32>,
+    // Pagination par clé, du plus récent au plus ancien
+    pub curseur: Option<String>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::role::Role;
+
+    fn utilisateur() -> Utilisateur {
+        let maintenant = Utc::now();
+        Utilisateur {
+            id: Uuid::new_v4(),
+            email: "jean.dupont@exemple.fr".to_string(),
+            mot_de_passe: "hache".to_string(),
+            prenom: "Jean".to_string(),
+            nom: "Dupont".to_string(),
+            role: Role::Client,
+            email_verifie: false,
+            date_creation: maintenant,
+            date_update: maintenant,
+            date_suppression: None,
+        }
+    }
+
+    #[test]
+    fn donnees_personnelles_masquees() {
+        let avant = utilisateur();
+        let apres = Utilisateur {
+            email: "jd@exemple.fr".to_string(),
+            nom: "Durand".to_string(),
+            mot_de_passe: "autre".to_string(),
+            email_verifie: true,
+            ..avant.clone()
+        };
+        let changements = changements_utilisateur(Some(&avant), Some(&apres));
+        assert_eq!(changements["email"], json!({ "avant": MASQUE, "apres": MASQUE }));
+        assert_eq!(changements["nom"], json!({ "avant": MASQUE, "apres": MASQUE }));
+        assert_eq!(changements["mot_de_passe"], json!({ "avant": MASQUE, "apres": MASQUE }));
+        assert_eq!(changements["email_verifie"], json!({ "avant": false, "apres": true }));
+        assert!(changements.get("prenom").is_none());
+        assert!(!changements.to_string().contains("exemple.fr"));
+    }
+
+    #[test]
+    fn creation_sans_valeur_anterieure() {
+        let changements = changements_utilisateur(None, Some(&utilisateur()));
+        assert_eq!(changements["prenom"], json!({ "avant": null, "apres": MASQUE }));
+        assert_eq!(changements["role"], json!({ "avant": null, "apres": "client" }));
+    }
+}
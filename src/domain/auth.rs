@@ -0,0 +1,208 @@
+use std::env;
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::error::MyError;
+use crate::domain::user::Utilisateur;
+use crate::domain::role::Role;
+use crate::domain::mot_de_passe::politique_mot_de_passe;
+use crate::domain::totp::DeuxFacteursRequis;
+use crate::domain::usurpation::JetonUsurpation;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeJeton {
+    Acces,
+    // Mot de passe vérifié, code du second facteur attendu
+    DeuxFacteurs,
+}
+
+// Contenu signé des jetons JWT
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub role: Role,
+    pub typ: TypeJeton,
+    pub jti: Uuid,
+    // Session d'appareil : sa révocation invalide aussitôt le jeton d'accès
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    // Administrateur agissant en tant que `sub` (usurpation d'identité pour le support)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usurpateur: Option<Uuid>,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Connexion {
+    pub email: String,
+    pub mot_de_passe: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Rafraichissement {
+    pub jeton_rafraichissement: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandeReinitialisation {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct Reinitialisation {
+    pub jeton: String,
+    #[validate(custom = "politique_mot_de_passe")]
+    pub mot_de_passe: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandeLienMagique {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnexionLienMagique {
+    pub jeton: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfirmationEmail {
+    pub jeton: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaireJetons {
+    pub jeton_acces: String,
+    pub jeton_rafraichissement: String,
+    pub type_jeton: String,
+    pub expire_dans: i64,
+}
+
+pub struct ServiceJwt {
+    encodage: EncodingKey,
+    decodage: DecodingKey,
+    duree_acces: Duration,
+    duree_deux_facteurs: Duration,
+    duree_usurpation: Duration,
+}
+
+impl ServiceJwt {
+    pub fn new(
+        secret: &[u8],
+        duree_acces: Duration,
+        duree_deux_facteurs: Duration,
+        duree_usurpation: Duration,
+    ) -> Self {
+        Self {
+            encodage: EncodingKey::from_secret(secret),
+            decodage: DecodingKey::from_secret(secret),
+            duree_acces,
+            duree_deux_facteurs,
+            duree_usurpation,
+        }
+    }
+
+    // JWT_SECRET est obligatoire, les durées (en secondes) sont optionnelles
+    pub fn depuis_env() -> Result<Self, MyError> {
+        let secret = env::var("JWT_SECRET")
+            .map_err(|_| MyError::Custom("JWT_SECRET must be set".to_string()))?;
+        let duree = |cle: &str, defaut: i64| {
+            env::var(cle)
+                .ok()
+                .and_then(|valeur| valeur.parse().ok())
+                .map(Duration::seconds)
+                .unwrap_or_else(|| Duration::seconds(defaut))
+        };
+        Ok(Self::new(
+            secret.as_bytes(),
+            duree("JWT_ACCES_DUREE_SECS", 15 * 60),
+            duree("JWT_DEUX_FACTEURS_DUREE_SECS", 5 * 60),
+            duree("JWT_USURPATION_DUREE_SECS", 10 * 60),
+        ))
+    }
+
+    pub fn duree_usurpation(&self) -> Duration {
+        self.duree_usurpation
+    }
+
+    // Le jeton de rafraîchissement est opaque : il est généré et stocké par la session d'appareil
+    pub fn emettre(
+        &self,
+        utilisateur: &Utilisateur,
+        session_id: Uuid,
+        jeton_rafraichissement: String,
+    ) -> Result<PaireJetons, MyError> {
+        Ok(PaireJetons {
+            jeton_acces: self.signer(utilisateur, TypeJeton::Acces, Some(session_id), None, self.duree_acces)?,
+            jeton_rafraichissement,
+            type_jeton: "Bearer".to_string(),
+            expire_dans: self.duree_acces.num_seconds(),
+        })
+    }
+
+    // Jeton intermédiaire de courte durée, échangé contre une paire après le code TOTP
+    pub fn emettre_deux_facteurs(&self, utilisateur: &Utilisateur) -> Result<DeuxFacteursRequis, MyError> {
+        Ok(DeuxFacteursRequis {
+            deux_facteurs_requis: true,
+            jeton_deux_facteurs: self.signer(utilisateur, TypeJeton::DeuxFacteurs, None, None, self.duree_deux_facteurs)?,
+            expire_dans: self.duree_deux_facteurs.num_seconds(),
+        })
+    }
+
+    // Jeton d'accès au nom de `cible`, rattaché à la session d'usurpation de l'administrateur :
+    // la fermer, ou fermer la session dont elle dépend, met fin à l'usurpation
+    pub fn emettre_usurpation(
+        &self,
+        cible: &Utilisateur,
+        usurpateur_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<JetonUsurpation, MyError> {
+        Ok(JetonUsurpation {
+            jeton_acces: self.signer(cible, TypeJeton::Acces, Some(session_id), Some(usurpateur_id), self.duree_usurpation)?,
+            type_jeton: "Bearer".to_string(),
+            expire_dans: self.duree_usurpation.num_seconds(),
+            utilisateur_id: cible.id,
+            usurpateur_id,
+        })
+    }
+
+    fn signer(
+        &self,
+        utilisateur: &Utilisateur,
+        typ: TypeJeton,
+        sid: Option<Uuid>,
+        usurpateur: Option<Uuid>,
+        duree: Duration,
+    ) -> Result<String, MyError> {
+        let now = Utc::now();
+        let claims = Claims {
+            sub: utilisateur.id,
+            role: utilisateur.role,
+            typ,
+            jti: Uuid::new_v4(),
+            sid,
+            usurpateur,
+            iat: now.timestamp(),
+            exp: (now + duree).timestamp(),
+        };
+        encode(&Header::default(), &claims, &self.encodage)
+            .map_err(|e| MyError::Custom(format!("Signature du jeton impossible: {}", e)))
+    }
+
+    // Vérifie signature, expiration et type ; la révocation est suivie en base (sessions, jetons à usage unique)
+    pub fn valider(&self, jeton: &str, attendu: TypeJeton) -> Result<Claims, MyError> {
+        let claims = decode::<Claims>(jeton, &self.decodage, &Validation::default())
+            .map_err(|_| MyError::Unauthorized("Jeton invalide ou expiré".to_string()))?
+            .claims;
+        if claims.typ != attendu {
+            return Err(MyError::Unauthorized("Type de jeton inattendu".to_string()));
+        }
+        Ok(claims)
+    }
+}
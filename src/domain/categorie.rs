@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::Categorie;
+
+
+// Longueurs alignées sur les VARCHAR de la table categories
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreationCategorie {
+    #[validate(length(min = 1, max = 50, message = "Entre 1 et 50 caractères"))]
+    pub nom: String,
+    pub description: Option<String>,
+    // Absent : catégorie racine
+    pub parent_id: Option<Uuid>,
+}
+
+// Remplacement des champs descriptifs (PUT) ; le parent change par DeplacementCategorie
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct MiseAJourCategorie {
+    #[validate(length(min = 1, max = 50, message = "Entre 1 et 50 caractères"))]
+    pub nom: String,
+    pub description: Option<String>,
+}
+
+// Déplacement d'une catégorie et de tout son sous-arbre ; null : à la racine
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeplacementCategorie {
+    pub parent_id: Option<Uuid>,
+}
+
+// Catégorie et ses descendants, pour GET /categories
+#[derive(Debug, Serialize, Clone)]
+pub struct NoeudCategorie {
+    #[serde(flatten)]
+    pub categorie: Categorie,
+    pub enfants: Vec<NoeudCategorie>,
+}
+
+impl NoeudCategorie {
+    // Forêt des catégories racines ; l'ordre des frères est celui de la liste reçue
+    // Une catégorie dont le parent manque à la liste est placée à la racine plutôt que perdue
+    pub fn arbre(categories: Vec<Categorie>) -> Vec<NoeudCategorie> {
+        let ids: HashSet<Uuid> = categories.iter().map(|categorie| categorie.id).collect();
+        let mut par_parent: HashMap<Option<Uuid>, Vec<Categorie>> = HashMap::new();
+        for categorie in categories {
+            let parent_id = categorie.parent_id.filter(|parent_id| ids.contains(parent_id));
+            par_parent.entry(parent_id).or_default().push(categorie);
+        }
+        Self::enfants_de(None, &mut par_parent)
+    }
+
+    fn enfants_de(parent_id: Option<Uuid>, par_parent: &mut HashMap<Option<Uuid>, Vec<Categorie>>) -> Vec<NoeudCategorie> {
+        par_parent
+            .remove(&parent_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|categorie| {
+                let enfants = Self::enfants_de(Some(categorie.id), par_parent);
+                NoeudCategorie { categorie, enfants }
+            })
+            .collect()
+    }
+}
+
+impl Categorie {
+    pub fn new(creation: CreationCategorie) -> Self {
+        Categorie {
+            id: Uuid::new_v4(),
+            nom: creation.nom,
+            description: creation.description,
+            parent_id: creation.parent_id,
+            date_creation: Utc::now(),
+        }
+    }
+
+    pub fn remplacer(&mut self, mise_a_jour: MiseAJourCategorie) {
+        self.nom = mise_a_jour.nom;
+        self.description = mise_a_jour.description;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categorie(nom: &str, parent: Option<&Categorie>) -> Categorie {
+        Categorie::new(CreationCategorie {
+            nom: nom.to_string(),
+            description: None,
+            parent_id: parent.map(|parent| parent.id),
+        })
+    }
+
+    fn noms(noeuds: &[NoeudCategorie]) -> Vec<&str> {
+        noeuds.iter().map(|noeud| noeud.categorie.nom.as_str()).collect()
+    }
+
+    #[test]
+    fn plusieurs_racines_et_enfants_imbriques() {
+        let maison = categorie("Maison", None);
+        let cuisine = categorie("Cuisine", Some(&maison));
+        let tasses = categorie("Tasses", Some(&cuisine));
+        let jardin = categorie("Jardin", None);
+        // Enfants reçus avant leur parent
+        let arbre = NoeudCategorie::arbre(vec![tasses, cuisine, jardin, maison]);
+
+        assert_eq!(noms(&arbre), ["Jardin", "Maison"]);
+        assert!(arbre[0].enfants.is_empty());
+        assert_eq!(noms(&arbre[1].enfants), ["Cuisine"]);
+        assert_eq!(noms(&arbre[1].enfants[0].enfants), ["Tasses"]);
+    }
+
+    #[test]
+    fn freres_dans_l_ordre_recu() {
+        let racine = categorie("Racine", None);
+        let enfants: Vec<Categorie> = ["B", "C", "A"].iter().map(|nom| categorie(nom, Some(&racine))).collect();
+        let mut categories = vec![racine];
+        categories.extend(enfants);
+
+        let arbre = NoeudCategorie::arbre(categories);
+        assert_eq!(noms(&arbre[0].enfants), ["B", "C", "A"]);
+    }
+
+    #[test]
+    fn orphelin_place_a_la_racine() {
+        let absente = categorie("Absente", None);
+        let orpheline = categorie("Orpheline", Some(&absente));
+        let petite_fille = categorie("Petite-fille", Some(&orpheline));
+        let racine = categorie("Racine", None);
+
+        let arbre = NoeudCategorie::arbre(vec![orpheline, racine, petite_fille]);
+        assert_eq!(noms(&arbre), ["Orpheline", "Racine"]);
+        assert_eq!(noms(&arbre[0].enfants), ["Petite-fille"]);
+        // Le parent d'origine reste visible dans la réponse
+        assert_eq!(arbre[0].categorie.parent_id, Some(absente.id));
+    }
+
+    #[test]
+    fn liste_vide() {
+        assert!(NoeudCategorie::arbre(Vec::new()).is_empty());
+    }
+}
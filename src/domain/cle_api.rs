@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::jeton::hacher_jeton;
+use crate::domain::role::Permission;
+
+// Préfixe commun à toutes les clés, pour les repérer dans un dépôt ou des logs
+const DEBUT_CLE: &str = "sk";
+
+// Table: cles_api
+#[derive(Debug, Serialize, Clone, FromRow)]
+pub struct CleApi {
+    pub id: Uuid,
+    pub utilisateur_id: Uuid,
+    pub nom: String,
+    // Partie visible de la clé ("sk_1a2b3c4d"), pour la reconnaître dans la liste
+    pub prefixe: String,
+    #[serde(skip)]
+    pub cle_hachee: String,
+    // Sous-ensemble des permissions du rôle, vérifié à chaque requête
+    pub portees: Json<Vec<Permission>>,
+    pub date_expiration: Option<DateTime<Utc>>,
+    pub date_derniere_utilisation: Option<DateTime<Utc>>,
+    pub date_revocation: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl CleApi {
+    // Renvoie la clé en clair (montrée une seule fois) et sa version stockée
+    pub fn new(creation: CreationCleApi) -> (String, Self) {
+        let mut identifiant = [0u8; 4];
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut identifiant);
+        rand::thread_rng().fill_bytes(&mut secret);
+        let prefixe = format!("{}_{}", DEBUT_CLE, hex::encode(identifiant));
+        let clair = format!("{}_{}", prefixe, hex::encode(secret));
+        let cle = CleApi {
+            id: Uuid::new_v4(),
+            utilisateur_id: creation.utilisateur_id,
+            nom: creation.nom,
+            prefixe,
+            cle_hachee: hacher_jeton(&clair),
+            portees: Json(creation.portees),
+            date_expiration: creation.date_expiration,
+            date_derniere_utilisation: None,
+            date_revocation: None,
+            date_creation: Utc::now(),
+        };
+        (clair, cle)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreationCleApi {
+    pub utilisateur_id: Uuid,
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
+    pub nom: String,
+    pub portees: Vec<Permission>,
+    pub date_expiration: Option<DateTime<Utc>>,
+}
+
+// Réponse de création : seule occasion de lire la clé en clair
+#[derive(Debug, Serialize, Clone)]
+pub struct CleApiCreee {
+    pub cle: String,
+    #[serde(flatten)]
+    pub details: CleApi,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequeteClesApi {
+    pub utilisateur_id: Uuid,
+}
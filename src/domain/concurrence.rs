@@ -0,0 +1,104 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::domain::error::MyError;
+
+// ETag fort dérivé de date_update (précision microseconde, celle de TIMESTAMPTZ)
+pub fn etag(date_update: DateTime<Utc>) -> String {
+    format!("\"{}\"", date_update.timestamp_micros())
+}
+
+// Condition d'écriture tirée de l'en-tête If-Match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Precondition {
+    // If-Match: * ou écriture interne : la ressource doit seulement exister
+    Toute,
+    // La version stockée doit être l'une de celles-ci
+    Versions(Vec<DateTime<Utc>>),
+}
+
+impl Precondition {
+    // If-Match absent : 428, pour ne pas écraser silencieusement une modification concurrente
+    pub fn depuis_if_match(valeur: Option<&str>) -> Result<Self, MyError> {
+        let valeur = valeur
+            .map(str::trim)
+            .filter(|valeur| !valeur.is_empty())
+            .ok_or_else(|| MyError::PreconditionRequise("En-tête If-Match requis".to_string()))?;
+        if valeur == "*" {
+            return Ok(Precondition::Toute);
+        }
+        // Comparaison forte : les ETags faibles (W/) et illisibles ne correspondent à rien
+        let versions = valeur
+            .split(',')
+            .filter_map(|etag| etag.trim().strip_prefix('"')?.strip_suffix('"')?.parse::<i64>().ok())
+            .filter_map(|micros| Utc.timestamp_micros(micros).single())
+            .collect();
+        Ok(Precondition::Versions(versions))
+    }
+
+    pub fn accepte(&self, date_update: DateTime<Utc>) -> bool {
+        match self {
+            Precondition::Toute => true,
+            Precondition::Versions(versions) => versions.contains(&date_update),
+        }
+    }
+
+    // Valeur liée à `date_update = ANY($n)`, None pour ne pas filtrer
+    pub fn versions(&self) -> Option<Vec<DateTime<Utc>>> {
+        match self {
+            Precondition::Toute => None,
+            Precondition::Versions(versions) => Some(versions.clone()),
+        }
+    }
+
+    pub fn verifier(&self, date_update: DateTime<Utc>) -> Result<(), MyError> {
+        if self.accepte(date_update) {
+            Ok(())
+        } else {
+            Err(MyError::PreconditionEchouee(
+                "La ressource a été modifiée depuis sa lecture".to_string(),
+            ))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(micros: i64) -> DateTime<Utc> {
+        Utc.timestamp_micros(micros).unwrap()
+    }
+
+    #[test]
+    fn etag_de_la_version() {
+        let version = date(1_760_000_000_123_456);
+        assert_eq!(etag(version), "\"1760000000123456\"");
+        let precondition = Precondition::depuis_if_match(Some(&etag(version))).unwrap();
+        assert!(precondition.accepte(version));
+        assert!(!precondition.accepte(date(1_760_000_000_123_457)));
+    }
+
+    #[test]
+    fn if_match_absent_ou_vide() {
+        assert!(matches!(Precondition::depuis_if_match(None), Err(MyError::PreconditionRequise(_))));
+        assert!(matches!(Precondition::depuis_if_match(Some("  ")), Err(MyError::PreconditionRequise(_))));
+    }
+
+    #[test]
+    fn if_match_etoile_ou_liste() {
+        assert_eq!(Precondition::depuis_if_match(Some("*")).unwrap(), Precondition::Toute);
+        assert_eq!(Precondition::Toute.versions(), None);
+        let precondition = Precondition::depuis_if_match(Some("\"1\", \"2\"")).unwrap();
+        assert_eq!(precondition.versions(), Some(vec![date(1), date(2)]));
+        assert!(precondition.verifier(date(2)).is_ok());
+        assert!(matches!(precondition.verifier(date(3)), Err(MyError::PreconditionEchouee(_))));
+    }
+
+    #[test]
+    fn etags_faibles_ou_illisibles_ignores() {
+        let precondition = Precondition::depuis_if_match(Some("W/\"1\", \"abc\", 2")).unwrap();
+        assert_eq!(precondition, Precondition::Versions(Vec::new()));
+        assert!(!precondition.accepte(date(1)));
+    }
+}
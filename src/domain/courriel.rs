@@ -0,0 +1,56 @@
+use chrono::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Courriel {
+    pub destinataire: String,
+    pub sujet: String,
+    pub corps: String,
+}
+
+impl Courriel {
+    pub fn reinitialisation_mot_de_passe(destinataire: &str, lien: &str, validite: Duration) -> Self {
+        Courriel {
+            destinataire: destinataire.to_string(),
+            sujet: "Réinitialisation de votre mot de passe".to_string(),
+            corps: format!(
+                "Bonjour,\n\n\
+                 Une réinitialisation du mot de passe a été demandée pour votre compte.\n\
+                 Pour choisir un nouveau mot de passe, suivez ce lien (valable {} minutes) :\n\n\
+                 {}\n\n\
+                 Si vous n'êtes pas à l'origine de cette demande, ignorez ce message.\n",
+                validite.num_minutes(),
+                lien
+            ),
+        }
+    }
+
+    pub fn lien_magique(destinataire: &str, lien: &str, validite: Duration) -> Self {
+        Courriel {
+            destinataire: destinataire.to_string(),
+            sujet: "Votre lien de connexion".to_string(),
+            corps: format!(
+                "Bonjour,\n\n\
+                 Pour vous connecter sans mot de passe, suivez ce lien (valable {} minutes, utilisable une seule fois) :\n\n\
+                 {}\n\n\
+                 Si vous n'êtes pas à l'origine de cette demande, ignorez ce message.\n",
+                validite.num_minutes(),
+                lien
+            ),
+        }
+    }
+
+    pub fn verification_email(destinataire: &str, lien: &str, validite: Duration) -> Self {
+        Courriel {
+            destinataire: destinataire.to_string(),
+            sujet: "Confirmez votre adresse email".to_string(),
+            corps: format!(
+                "Bonjour,\n\n\
+                 Pour confirmer votre adresse email, suivez ce lien (valable {} heures) :\n\n\
+                 {}\n\n\
+                 Si vous n'avez pas créé de compte, ignorez ce message.\n",
+                validite.num_hours(),
+                lien
+            ),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::domain::error::MyError;
+use crate::domain::mot_de_passe::HacheurMotDePasse;
+
+// Incrémentée à chaque changement de structure de l'archive
+pub const VERSION_EXPORT: u32 = 1;
+
+// Archive RGPD (droit d'accès et portabilité) : tout ce qui est rattaché au compte, hors secrets
+#[derive(Debug, Serialize)]
+pub struct ExportDonnees {
+    pub version: u32,
+    pub genere_le: DateTime<Utc>,
+    pub utilisateur_id: Uuid,
+    pub donnees: Value,
+}
+
+impl ExportDonnees {
+    pub fn new(utilisateur_id: Uuid, donnees: Value) -> Self {
+        ExportDonnees {
+            version: VERSION_EXPORT,
+            genere_le: Utc::now(),
+            utilisateur_id,
+            donnees,
+        }
+    }
+}
+
+// Valeurs qui remplacent les données personnelles d'un compte effacé
+// Le compte est conservé : les commandes le référencent et restent nécessaires à la comptabilité
+#[derive(Debug, Clone)]
+pub struct Anonymisation {
+    pub email: String,
+    pub prenom: String,
+    pub nom: String,
+    // Hash d'un secret aléatoire jeté aussitôt : aucune connexion possible
+    pub mot_de_passe: String,
+}
+
+impl Anonymisation {
+    pub async fn pour(utilisateur_id: Uuid, hacheur: &HacheurMotDePasse) -> Result<Self, MyError> {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Ok(Anonymisation {
+            // Unique par compte, pour respecter l'index d'unicité
+            email: format!("efface-{}@anonyme.invalid", utilisateur_id),
+            prenom: "Anonyme".to_string(),
+            nom: "Anonyme".to_string(),
+            mot_de_passe: hacheur.hacher(&hex::encode(secret)).await?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigRetention {
+    // Délai pendant lequel un compte supprimé reste restaurable
+    pub duree: Duration,
+    pub intervalle_purge: std::time::Duration,
+}
+
+impl ConfigRetention {
+    // RETENTION_SUPPRESSION_SECS (30 jours par défaut) et PURGE_SUPPRESSIONS_SECS (1 jour)
+    pub fn depuis_env() -> Self {
+        let secondes = |cle: &str, defaut: u64| {
+            env::var(cle)
+                .ok()
+                .and_then(|valeur| valeur.parse().ok())
+                .unwrap_or(defaut)
+        };
+        Self {
+            duree: Duration::seconds(secondes("RETENTION_SUPPRESSION_SECS", 30 * 24 * 3600) as i64),
+            intervalle_purge: std::time::Duration::from_secs(secondes("PURGE_SUPPRESSIONS_SECS", 24 * 3600)),
+        }
+    }
+}
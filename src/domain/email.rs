@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use crate::domain::user::Utilisateur;
+
+// Fournisseurs dont la partie locale admet des variantes qui désignent la même boîte
+#[derive(Debug)]
+pub struct RegleFournisseur {
+    // Nom utilisé dans EMAIL_REGLES_FOURNISSEURS
+    nom: &'static str,
+    domaines: &'static [&'static str],
+    // Domaine retenu pour toutes les variantes
+    canonique: &'static str,
+    // Les points de la partie locale sont ignorés (j.doe == jdoe)
+    ignorer_points: bool,
+    // Tout ce qui suit le premier « + » est une étiquette (jdoe+boutique == jdoe)
+    ignorer_etiquette: bool,
+}
+
+const REGLES_FOURNISSEURS: &[RegleFournisseur] = &[RegleFournisseur {
+    nom: "gmail",
+    domaines: &["gmail.com", "googlemail.com"],
+    canonique: "gmail.com",
+    ignorer_points: true,
+    ignorer_etiquette: true,
+}];
+
+// Adresse stockée, à laquelle les mails sont envoyés : espaces retirés, domaine en minuscules
+// La partie locale garde sa casse et ses variantes (points, étiquette)
+pub fn normaliser_email(email: &str) -> String {
+    let email = email.trim();
+    match email.rsplit_once('@') {
+        Some((local, domaine)) => format!("{}@{}", local, domaine.to_lowercase()),
+        None => email.to_string(),
+    }
+}
+
+// Règles des fournisseurs activées, appliquées à la seule clé d'unicité et de recherche
+#[derive(Debug, Clone, Default)]
+pub struct ReglesEmail {
+    fournisseurs: Vec<&'static RegleFournisseur>,
+}
+
+impl ReglesEmail {
+    pub fn new(noms: &[&str]) -> Self {
+        let fournisseurs = noms
+            .iter()
+            .map(|nom| nom.trim())
+            .filter(|nom| !nom.is_empty())
+            .filter_map(|nom| {
+                let regle = REGLES_FOURNISSEURS.iter().find(|regle| regle.nom.eq_ignore_ascii_case(nom));
+                if regle.is_none() {
+                    tracing::warn!("Règle de fournisseur email inconnue ignorée: {}", nom);
+                }
+                regle
+            })
+            .collect();
+        Self { fournisseurs }
+    }
+
+    // EMAIL_REGLES_FOURNISSEURS=gmail (aucune règle par défaut)
+    pub fn depuis_env() -> Self {
+        let liste = env::var("EMAIL_REGLES_FOURNISSEURS").unwrap_or_default();
+        Self::new(&liste.split(',').collect::<Vec<_>>())
+    }
+
+    // Clé stockée dans utilisateur.email_cle : adresse en minuscules, variantes du fournisseur ramenées à une seule
+    pub fn cle(&self, email: &str) -> String {
+        let email = normaliser_email(email).to_lowercase();
+        let Some((local, domaine)) = email.rsplit_once('@') else {
+            return email;
+        };
+        match self.fournisseurs.iter().find(|regle| regle.domaines.contains(&domaine)) {
+            Some(regle) => {
+                let mut local = local;
+                if regle.ignorer_etiquette && let Some((base, _)) = local.split_once('+') {
+                    local = base;
+                }
+                let local = if regle.ignorer_points { local.replace('.', "") } else { local.to_string() };
+                format!("{}@{}", local, regle.canonique)
+            }
+            None => email,
+        }
+    }
+}
+
+// Résultat de la normalisation des comptes existants
+#[derive(Debug, Default)]
+pub struct RapportNormalisation {
+    // (id, email actuel, email normalisé)
+    pub a_normaliser: Vec<(uuid::Uuid, String, String)>,
+    // Comptes qui deviennent indiscernables une fois normalisés, à fusionner à la main
+    pub doublons: BTreeMap<String, Vec<Utilisateur>>,
+}
+
+impl RapportNormalisation {
+    pub fn depuis(utilisateurs: &[Utilisateur], regles: &ReglesEmail) -> Self {
+        let mut rapport = RapportNormalisation::default();
+        let mut groupes: BTreeMap<String, Vec<Utilisateur>> = BTreeMap::new();
+        for utilisateur in utilisateurs {
+            let normalise = normaliser_email(&utilisateur.email);
+            if normalise != utilisateur.email {
+                rapport.a_normaliser.push((utilisateur.id, utilisateur.email.clone(), normalise));
+            }
+            groupes.entry(regles.cle(&utilisateur.email)).or_default().push(utilisateur.clone());
+        }
+        rapport.doublons = groupes.into_iter().filter(|(_, comptes)| comptes.len() > 1).collect();
+        rapport
+    }
+
+    // Un compte en doublon n'est pas réécrit : l'index unique le refuserait
+    pub fn est_en_doublon(&self, email: &str, regles: &ReglesEmail) -> bool {
+        self.doublons.contains_key(&regles.cle(email))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adresse_stockee_garde_la_partie_locale() {
+        assert_eq!(normaliser_email("  Jean.Dupont+Boutique@GMail.COM "), "Jean.Dupont+Boutique@gmail.com");
+        assert_eq!(normaliser_email("sans-arobase"), "sans-arobase");
+    }
+
+    #[test]
+    fn cle_sans_regle_en_minuscules() {
+        let regles = ReglesEmail::default();
+        assert_eq!(regles.cle("Jean.Dupont+x@GMail.com"), "jean.dupont+x@gmail.com");
+    }
+
+    #[test]
+    fn cle_avec_regle_gmail() {
+        let regles = ReglesEmail::new(&["gmail"]);
+        assert_eq!(regles.cle("Jean.Dupont+boutique@googlemail.com"), "jeandupont@gmail.com");
+        assert_eq!(regles.cle("jeandupont@gmail.com"), "jeandupont@gmail.com");
+        // Les autres domaines ne sont pas concernés
+        assert_eq!(regles.cle("jean.dupont+x@exemple.fr"), "jean.dupont+x@exemple.fr");
+    }
+
+    #[test]
+    fn regle_inconnue_ignoree() {
+        let regles = ReglesEmail::new(&["inconnu", " "]);
+        assert_eq!(regles.cle("j.d@gmail.com"), "j.d@gmail.com");
+    }
+
+    fn utilisateur(email: &str) -> Utilisateur {
+        let maintenant = chrono::Utc::now();
+        Utilisateur {
+            id: uuid::Uuid::new_v4(),
+            email: email.to_string(),
+            mot_de_passe: String::new(),
+            prenom: "Jean".to_string(),
+            nom: "Dupont".to_string(),
+            role: crate::domain::role::Role::Client,
+            email_verifie: false,
+            date_creation: maintenant,
+            date_update: maintenant,
+            date_suppression: None,
+        }
+    }
+
+    #[test]
+    fn rapport_signale_les_doublons() {
+        let regles = ReglesEmail::new(&["gmail"]);
+        let comptes = [utilisateur("J.Dupont@GMAIL.com"), utilisateur("jdupont@gmail.com"), utilisateur("autre@exemple.fr")];
+        let rapport = RapportNormalisation::depuis(&comptes, &regles);
+        assert_eq!(rapport.a_normaliser.len(), 1);
+        assert_eq!(rapport.a_normaliser[0].2, "J.Dupont@gmail.com");
+        assert_eq!(rapport.doublons.len(), 1);
+        assert!(rapport.est_en_doublon("j.dupont+x@gmail.com", &regles));
+        assert!(!rapport.est_en_doublon("autre@exemple.fr", &regles));
+    }
+}
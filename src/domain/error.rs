@@ -1,6 +1,15 @@
-use actix_web::{http::StatusCode, ResponseError};
+use actix_web::{http::{header, StatusCode}, HttpResponse, ResponseError};
 use serde::Serialize;
 use std::fmt;
+use validator::ValidationErrors;
+
+// Un champ refusé par la validation, avec la raison
+#[derive(Debug, Serialize)]
+pub struct ChampInvalide {
+    pub champ: String,
+    pub code: String,
+    pub message: String,
+}
 
 #[derive(Debug, Serialize)]
 pub enum MyError {
@@ -8,7 +17,18 @@ pub enum MyError {
     BadRequest(String),
     NotFound(String),
     Unauthorized(String),
+    Forbidden(String),
     Validation(String),
+    ChampsInvalides(Vec<ChampInvalide>),
+    // reessayer_dans en secondes, renvoyé aussi dans l'en-tête Retry-After
+    TropDeTentatives { message: String, reessayer_dans: i64 },
+    CompteVerrouille { message: String, reessayer_dans: i64 },
+    // Écriture conditionnelle : version périmée (If-Match) ou en-tête absent
+    PreconditionEchouee(String),
+    PreconditionRequise(String),
+    TypeNonSupporte(String),
+    // Fournisseur d'identité externe injoignable ou réponse inexploitable
+    FournisseurIndisponible(String),
     Custom(String),
 }
 
@@ -19,7 +39,22 @@ impl fmt::Display for MyError {
             MyError::BadRequest(msg ) =>  write!(f, "bad reqwest : {}", msg),
             MyError::NotFound(msg) => write!(f, "Not found: {}", msg),
             MyError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            MyError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             MyError::Validation(msg) => write!(f, "Validation error: {}", msg),
+            MyError::ChampsInvalides(champs) => {
+                let champs: Vec<&str> = champs.iter().map(|c| c.champ.as_str()).collect();
+                write!(f, "Invalid fields: {}", champs.join(", "))
+            }
+            MyError::TropDeTentatives { message, reessayer_dans } => {
+                write!(f, "Too many requests: {} (retry in {}s)", message, reessayer_dans)
+            }
+            MyError::CompteVerrouille { message, reessayer_dans } => {
+                write!(f, "Locked: {} (retry in {}s)", message, reessayer_dans)
+            }
+            MyError::PreconditionEchouee(msg) => write!(f, "Precondition failed: {}", msg),
+            MyError::PreconditionRequise(msg) => write!(f, "Precondition required: {}", msg),
+            MyError::TypeNonSupporte(msg) => write!(f, "Unsupported media type: {}", msg),
+            MyError::FournisseurIndisponible(msg) => write!(f, "Bad gateway: {}", msg),
             MyError::Custom(msg) => write!(f, "Custom error: {}", msg),
         }
     }
@@ -32,9 +67,48 @@ impl ResponseError for MyError {
             MyError::BadRequest(_) => StatusCode::BAD_REQUEST,
             MyError::NotFound(_) => StatusCode::NOT_FOUND,
             MyError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            MyError::Forbidden(_) => StatusCode::FORBIDDEN,
             MyError::Validation(_) => StatusCode::BAD_REQUEST,
+            MyError::ChampsInvalides(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            MyError::TropDeTentatives { .. } => StatusCode::TOO_MANY_REQUESTS,
+            MyError::CompteVerrouille { .. } => StatusCode::LOCKED,
+            MyError::PreconditionEchouee(_) => StatusCode::PRECONDITION_FAILED,
+            MyError::PreconditionRequise(_) => StatusCode::PRECONDITION_REQUIRED,
+            MyError::TypeNonSupporte(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            MyError::FournisseurIndisponible(_) => StatusCode::BAD_GATEWAY,
             MyError::Custom(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
+    // Même corps JSON que les handlers qui construisent la réponse eux-mêmes
+    fn error_response(&self) -> HttpResponse {
+        let mut reponse = HttpResponse::build(self.status_code());
+        if let MyError::TropDeTentatives { reessayer_dans, .. } | MyError::CompteVerrouille { reessayer_dans, .. } = self {
+            reponse.insert_header((header::RETRY_AFTER, reessayer_dans.to_string()));
+        }
+        reponse.json(self)
+    }
+
+}
+
+impl From<ValidationErrors> for MyError {
+    fn from(erreurs: ValidationErrors) -> Self {
+        let mut champs: Vec<ChampInvalide> = erreurs
+            .field_errors()
+            .into_iter()
+            .flat_map(|(champ, erreurs)| {
+                erreurs.iter().map(move |erreur| ChampInvalide {
+                    champ: champ.to_string(),
+                    code: erreur.code.to_string(),
+                    message: erreur
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| erreur.code.to_string()),
+                })
+            })
+            .collect();
+        champs.sort_by(|a, b| a.champ.cmp(&b.champ));
+        MyError::ChampsInvalides(champs)
+    }
 }
@@ -0,0 +1,101 @@
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Colonne jetons_usage_unique.objet
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ObjetJeton {
+    ReinitialisationMotDePasse,
+    VerificationEmail,
+    // Connexion sans mot de passe par lien envoyé par mail
+    ConnexionLienMagique,
+    // Jeton intermédiaire du double facteur (jti du JWT), enregistré à son échange
+    DeuxFacteurs,
+}
+
+// Table: jetons_usage_unique
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct JetonUsageUnique {
+    pub id: Uuid,
+    pub utilisateur_id: Uuid,
+    pub objet: ObjetJeton,
+    pub jeton_hache: String,
+    pub date_expiration: DateTime<Utc>,
+    pub date_utilisation: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl JetonUsageUnique {
+    // Renvoie le jeton en clair (à transmettre à l'utilisateur) et sa version stockée
+    pub fn new(utilisateur_id: Uuid, objet: ObjetJeton, duree: Duration) -> (String, Self) {
+        let mut octets = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut octets);
+        let clair = hex::encode(octets);
+        let now = Utc::now();
+        let jeton = JetonUsageUnique {
+            id: Uuid::new_v4(),
+            utilisateur_id,
+            objet,
+            jeton_hache: hacher_jeton(&clair),
+            date_expiration: now + duree,
+            date_utilisation: None,
+            date_creation: now,
+        };
+        (clair, jeton)
+    }
+
+    // Jeton émis sans être stocké (jti d'un JWT), enregistré comme utilisé au moment où il sert
+    pub fn utilise(utilisateur_id: Uuid, objet: ObjetJeton, identifiant: &str, date_expiration: DateTime<Utc>) -> Self {
+        let now = Utc::now();
+        JetonUsageUnique {
+            id: Uuid::new_v4(),
+            utilisateur_id,
+            objet,
+            jeton_hache: hacher_jeton(identifiant),
+            date_expiration,
+            date_utilisation: Some(now),
+            date_creation: now,
+        }
+    }
+}
+
+// Les jetons sont aléatoires sur 256 bits : un SHA-256 suffit, bcrypt serait superflu
+pub fn hacher_jeton(clair: &str) -> String {
+    hex::encode(Sha256::digest(clair.as_bytes()))
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigJetons {
+    // Base des liens envoyés par mail (front-end)
+    pub url_application: String,
+    pub duree_reinitialisation: Duration,
+    pub duree_verification: Duration,
+    pub duree_lien_magique: Duration,
+    pub duree_code_sms: Duration,
+}
+
+impl ConfigJetons {
+    pub fn depuis_env() -> Self {
+        let secondes = |cle: &str, defaut: i64| {
+            env::var(cle)
+                .ok()
+                .and_then(|valeur| valeur.parse().ok())
+                .map(Duration::seconds)
+                .unwrap_or_else(|| Duration::seconds(defaut))
+        };
+        Self {
+            url_application: env::var("APP_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string()),
+            duree_reinitialisation: secondes("REINITIALISATION_DUREE_SECS", 3600),
+            duree_verification: secondes("VERIFICATION_EMAIL_DUREE_SECS", 48 * 3600),
+            duree_lien_magique: secondes("LIEN_MAGIQUE_DUREE_SECS", 15 * 60),
+            duree_code_sms: secondes("CODE_SMS_DUREE_SECS", 5 * 60),
+        }
+    }
+}
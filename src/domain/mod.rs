@@ -1,3 +1,24 @@
 pub mod models;
 pub mod  user;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod mot_de_passe;
+pub mod auth;
+pub mod session;
+pub mod role;
+pub mod pagination;
+pub mod jeton;
+pub mod courriel;
+pub mod verification;
+pub mod tentatives;
+pub mod totp;
+pub mod concurrence;
+pub mod email;
+pub mod donnees_personnelles;
+pub mod audit;
+pub mod usurpation;
+pub mod cle_api;
+pub mod oidc;
+pub mod sms;
+pub mod produit;
+pub mod montant;
+pub mod categorie;
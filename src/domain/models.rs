@@ -1,11 +1,19 @@
+// Miroir du schéma SQL : les modèles sont branchés au fur et à mesure des ports
+
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
 use uuid::Uuid;
 
+use crate::domain::error::MyError;
+use crate::domain::montant::{Devise, Montant};
+
 
 // Table: utilisateurs
 #[derive(Debug, Serialize, Deserialize, Clone, FromRow, PartialEq, Eq)]
+#[allow(dead_code)]
 pub struct User {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub email: String, // VARCHAR(255), NOT NULL, UNIQUE
@@ -24,6 +32,9 @@ pub struct Session {
     pub utilisateur_id: Option<Uuid>, // UUID, REFERENCES utilisateurs(id)
     pub date_expiration: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
+    pub appareil: Option<String>, // VARCHAR(255), User-Agent à la connexion
+    pub adresse_ip: Option<String>, // VARCHAR(45)
+    pub derniere_activite: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
 }
 
 // Table: categories
@@ -32,17 +43,18 @@ pub struct Categorie {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub nom: String, // VARCHAR(50), NOT NULL
     pub description: Option<String>, // TEXT
+    pub parent_id: Option<Uuid>, // UUID, REFERENCES categories(id), ON DELETE RESTRICT
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
 }
 
 // Table: produits
-#[derive(Debug, Clone , Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone , Serialize, Deserialize)]
 pub struct Produit {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub nom: String, // VARCHAR(100), NOT NULL
     pub description: String, // TEXT, NOT NULL
     pub reference: String, // VARCHAR(50), UNIQUE, NOT NULL
-    pub prix: String, // DECIMAL(12, 2), NOT NULL, CHECK (prix > 0)
+    pub prix: Montant, // DECIMAL(12, 2), NOT NULL, CHECK (prix > 0)
     pub quantite: i32, // INTEGER, NOT NULL, DEFAULT 0, CHECK (quantite >= 0)
     pub categorie_id: Option<Uuid>, // UUID, REFERENCES categories(id), ON DELETE SET NULL
     pub image_principale_url: Option<String>, // VARCHAR(255)
@@ -50,21 +62,55 @@ pub struct Produit {
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
 }
 
+// Prix du catalogue : la table n'a pas de colonne devise
+impl<'r> FromRow<'r, PgRow> for Produit {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Produit {
+            id: row.try_get("id")?,
+            nom: row.try_get("nom")?,
+            description: row.try_get("description")?,
+            reference: row.try_get("reference")?,
+            prix: Montant::lire(row, "prix", Devise::default())?,
+            quantite: row.try_get("quantite")?,
+            categorie_id: row.try_get("categorie_id")?,
+            image_principale_url: row.try_get("image_principale_url")?,
+            est_publie: row.try_get("est_publie")?,
+            date_creation: row.try_get("date_creation")?,
+        })
+    }
+}
+
 // Table: variantes_produit
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct VarianteProduit {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub produit_id: Uuid, // UUID, NOT NULL, REFERENCES produits(id), ON DELETE CASCADE
     pub nom: String, // VARCHAR(50), NOT NULL
     pub valeur: String, // VARCHAR(50), NOT NULL
-    pub prix_ajuste: String, // DECIMAL(10, 2), DEFAULT 0.00
+    pub prix_ajuste: Option<Montant>, // DECIMAL(10, 2), DEFAULT 0.00
     pub quantite: i32, // INTEGER, NOT NULL, DEFAULT 0, CHECK (quantite >= 0)
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
     // UNIQUE(produit_id, nom, valeur)
 }
 
+impl<'r> FromRow<'r, PgRow> for VarianteProduit {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(VarianteProduit {
+            id: row.try_get("id")?,
+            produit_id: row.try_get("produit_id")?,
+            nom: row.try_get("nom")?,
+            valeur: row.try_get("valeur")?,
+            prix_ajuste: Montant::lire_option(row, "prix_ajuste", Devise::default())?,
+            quantite: row.try_get("quantite")?,
+            date_creation: row.try_get("date_creation")?,
+        })
+    }
+}
+
 // Table: reviews
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct Review {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub produit_id: Uuid, // UUID, NOT NULL, REFERENCES produits(id), ON DELETE CASCADE
@@ -77,6 +123,7 @@ pub struct Review {
 
 // Table: wisrhlist
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct Wishlist {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub utilisateur_id: Uuid, // UUID, NOT NULL, REFERENCES utilisateurs(id)
@@ -86,21 +133,59 @@ pub struct Wishlist {
 }
 
 // Table: promotions
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct Promotion {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub code: String, // VARCHAR(20), NOT NULL, UNIQUE
     pub description: Option<String>, // TEXT
-    pub pourcentage_remise: Option<String>, // DECIMAL(5, 2), CHECK (pourcentage_remise >= 0 AND pourcentage_remise <= 100)
-    pub montant_remise: Option<String>, // DECIMAL(12, 2), CHECK (montant_remise >= 0)
+    pub pourcentage_remise: Option<Decimal>, // DECIMAL(5, 2), CHECK (pourcentage_remise >= 0 AND pourcentage_remise <= 100)
+    pub montant_remise: Option<Montant>, // DECIMAL(12, 2), CHECK (montant_remise >= 0), dans la colonne devise
     pub date_debut: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL
     pub date_fin: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
     // CHECK (pourcentage_remise IS NOT NULL OR montant_remise IS NOT NULL)
 }
 
+impl<'r> FromRow<'r, PgRow> for Promotion {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let devise: Devise = row.try_get("devise")?;
+        Ok(Promotion {
+            id: row.try_get("id")?,
+            code: row.try_get("code")?,
+            description: row.try_get("description")?,
+            pourcentage_remise: row.try_get("pourcentage_remise")?,
+            montant_remise: Montant::lire_option(row, "montant_remise", devise)?,
+            date_debut: row.try_get("date_debut")?,
+            date_fin: row.try_get("date_fin")?,
+            date_creation: row.try_get("date_creation")?,
+        })
+    }
+}
+
+// Sans appelant tant que les commandes n'ont pas de port
+#[allow(dead_code)]
+impl Promotion {
+    // Pourcentage puis montant fixe, cumulés et plafonnés au sous-total
+    pub fn remise(&self, sous_total: &Montant) -> Result<Montant, MyError> {
+        let mut remise = Montant::zero(sous_total.devise());
+        if let Some(pourcentage) = self.pourcentage_remise {
+            remise = remise.plus(&sous_total.pourcentage(pourcentage)?)?;
+        }
+        if let Some(montant) = &self.montant_remise {
+            remise = remise.plus(montant)?;
+        }
+        remise.min(*sous_total)
+    }
+
+    pub fn appliquer(&self, sous_total: &Montant) -> Result<Montant, MyError> {
+        sous_total.moins(&self.remise(sous_total)?)
+    }
+}
+
 // Table: methodes_paiement
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct MethodePaiement {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub nom: String, // VARCHAR(50), NOT NULL
@@ -109,18 +194,33 @@ pub struct MethodePaiement {
 }
 
 // Table: methodes_livraison
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct MethodeLivraison {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub nom: String, // VARCHAR(50), NOT NULL
     pub description: Option<String>, // TEXT
-    pub cout: String, // DECIMAL(10, 2), NOT NULL, DEFAULT 0.00
+    pub cout: Montant, // DECIMAL(10, 2), NOT NULL, DEFAULT 0.00
     pub delai_estime: Option<String>, // VARCHAR(50)
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
 }
 
+impl<'r> FromRow<'r, PgRow> for MethodeLivraison {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(MethodeLivraison {
+            id: row.try_get("id")?,
+            nom: row.try_get("nom")?,
+            description: row.try_get("description")?,
+            cout: Montant::lire(row, "cout", Devise::default())?,
+            delai_estime: row.try_get("delai_estime")?,
+            date_creation: row.try_get("date_creation")?,
+        })
+    }
+}
+
 // Table: adresses
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct Adresse {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub utilisateur_id: Uuid, // UUID, NOT NULL, REFERENCES utilisateurs(id)
@@ -137,6 +237,7 @@ pub struct Adresse {
 
 // Table: paniers_articles
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct PanierArticle {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub utilisateur_id: Option<Uuid>, // UUID, REFERENCES utilisateurs(id)
@@ -150,13 +251,14 @@ pub struct PanierArticle {
 }
 
 // Table: commandes
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct Commande {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub utilisateur_id: Option<Uuid>, // UUID, REFERENCES utilisateurs(id)
     pub numero_commande: String, // VARCHAR(20), UNIQUE, NOT NULL
     pub statut: CommandeStatut, // VARCHAR(20), NOT NULL, DEFAULT 'en_attente'
-    pub montant_total: String, // DECIMAL(12, 2), NOT NULL, CHECK (montant_total >= 0)
+    pub montant_total: Montant, // DECIMAL(12, 2), NOT NULL, CHECK (montant_total >= 0)
     pub promotion_id: Option<Uuid>, // UUID, REFERENCES promotions(id)
     pub lieu_publique_proche: Uuid, // UUID, NOT NULL, REFERENCES adresses(id)
     pub methode_paiement_id: Option<Uuid>, // UUID, REFERENCES methodes_paiement(id)
@@ -165,13 +267,37 @@ pub struct Commande {
     pub methode_livraison_id: Option<Uuid>, // UUID, REFERENCES methodes_livraison(id)
     pub statut_livraison: Option<LivraisonStatut>, // VARCHAR(20), DEFAULT 'en_preparation'
     pub numero_suivi: Option<String>, // VARCHAR(100)
-    pub devise: String, // VARCHAR(3), NOT NULL, DEFAULT 'EUR'
+    pub devise: Devise, // VARCHAR(3), NOT NULL, DEFAULT 'EUR'
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
 }
 
+impl<'r> FromRow<'r, PgRow> for Commande {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let devise: Devise = row.try_get("devise")?;
+        Ok(Commande {
+            id: row.try_get("id")?,
+            utilisateur_id: row.try_get("utilisateur_id")?,
+            numero_commande: row.try_get("numero_commande")?,
+            statut: row.try_get("statut")?,
+            montant_total: Montant::lire(row, "montant_total", devise)?,
+            promotion_id: row.try_get("promotion_id")?,
+            lieu_publique_proche: row.try_get("lieu_publique_proche")?,
+            methode_paiement_id: row.try_get("methode_paiement_id")?,
+            statut_paiement: row.try_get("statut_paiement")?,
+            id_transaction: row.try_get("id_transaction")?,
+            methode_livraison_id: row.try_get("methode_livraison_id")?,
+            statut_livraison: row.try_get("statut_livraison")?,
+            numero_suivi: row.try_get("numero_suivi")?,
+            devise,
+            date_creation: row.try_get("date_creation")?,
+        })
+    }
+}
+
 // Enum for commandes.statut
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[allow(dead_code)]
 pub enum CommandeStatut {
     EnAttente,
     EnTraitement,
@@ -184,6 +310,7 @@ pub enum CommandeStatut {
 // Enum for commandes.statut_paiement
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[allow(dead_code)]
 pub enum PaiementStatut {
     EnAttente,
     Complete,
@@ -194,6 +321,7 @@ pub enum PaiementStatut {
 // Enum for commandes.statut_livraison
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[allow(dead_code)]
 pub enum LivraisonStatut {
     EnPreparation,
     Expediee,
@@ -203,20 +331,53 @@ pub enum LivraisonStatut {
 }
 
 // Table: articles_commande
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
 pub struct ArticleCommande {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub commande_id: Uuid, // UUID, NOT NULL, REFERENCES commandes(id), ON DELETE CASCADE
     pub produit_id: Uuid, // UUID, NOT NULL, REFERENCES produits(id)
     pub variante_id: Option<Uuid>, // UUID, REFERENCES variantes_produit(id)
     pub nom_produit: String, // VARCHAR(255), NOT NULL
-    pub prix_unitaire: String, // DECIMAL(12, 2), NOT NULL, CHECK (prix_unitaire >= 0)
+    pub prix_unitaire: Montant, // DECIMAL(12, 2), NOT NULL, CHECK (prix_unitaire >= 0)
     pub quantite: i32, // INTEGER, NOT NULL, CHECK (quantite > 0)
-    pub prix_total: String, // DECIMAL(12, 2), NOT NULL, CHECK (prix_total >= 0)
+    pub prix_total: Montant, // DECIMAL(12, 2), NOT NULL, CHECK (prix_total >= 0)
+}
+
+// Les lignes n'ont pas de devise : la requête joint orders.devise (SELECT oi.*, o.devise ...)
+impl<'r> FromRow<'r, PgRow> for ArticleCommande {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let devise: Devise = row.try_get("devise")?;
+        Ok(ArticleCommande {
+            id: row.try_get("id")?,
+            commande_id: row.try_get("commande_id")?,
+            produit_id: row.try_get("produit_id")?,
+            variante_id: row.try_get("variante_id")?,
+            nom_produit: row.try_get("nom_produit")?,
+            prix_unitaire: Montant::lire(row, "prix_unitaire", devise)?,
+            quantite: row.try_get("quantite")?,
+            prix_total: Montant::lire(row, "prix_total", devise)?,
+        })
+    }
+}
+
+// Sans appelant tant que les commandes n'ont pas de port
+#[allow(dead_code)]
+impl ArticleCommande {
+    // prix_total attendu pour la ligne
+    pub fn total_calcule(&self) -> Result<Montant, MyError> {
+        self.prix_unitaire.fois(self.quantite)
+    }
+
+    // Sous-total d'une commande, avant remise et livraison
+    pub fn sous_total(articles: &[ArticleCommande], devise: Devise) -> Result<Montant, MyError> {
+        Montant::somme(articles.iter().map(|article| &article.prix_total), devise)
+    }
 }
 
 // Table: notifications
 #[derive(Debug, Serialize, Deserialize, FromRow)]
+#[allow(dead_code)]
 pub struct Notification {
     pub id: Uuid, // PRIMARY KEY, DEFAULT uuid_generate_v4()
     pub utilisateur_id: Uuid, // UUID, NOT NULL, REFERENCES utilisateurs(id)
@@ -225,4 +386,68 @@ pub struct Notification {
     pub est_lue: bool, // BOOLEAN, NOT NULL, DEFAULT FALSE
     pub date_creation: DateTime<Utc>, // TIMESTAMPTZ, NOT NULL, DEFAULT CURRENT_TIMESTAMP
     pub metadata: Option<serde_json::Value>, // JSONB
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn promotion(pourcentage_remise: Option<&str>, montant_remise: Option<Montant>) -> Promotion {
+        Promotion {
+            id: Uuid::new_v4(),
+            code: "TEST".to_string(),
+            description: None,
+            pourcentage_remise: pourcentage_remise.map(|p| p.parse().unwrap()),
+            montant_remise,
+            date_debut: Utc::now(),
+            date_fin: Utc::now(),
+            date_creation: Utc::now(),
+        }
+    }
+
+    fn montant(valeur: &str, devise: Devise) -> Montant {
+        Montant::new(valeur.parse().unwrap(), devise)
+    }
+
+    #[test]
+    fn remise_plafonnee_au_sous_total() {
+        let sous_total = montant("30.00", Devise::Eur);
+        let promotion = promotion(None, Some(montant("50.00", Devise::Eur)));
+        assert_eq!(promotion.remise(&sous_total).unwrap(), sous_total);
+        assert_eq!(promotion.appliquer(&sous_total).unwrap(), Montant::zero(Devise::Eur));
+    }
+
+    #[test]
+    fn remise_en_pourcentage_dans_la_devise_de_la_commande() {
+        let sous_total = montant("80.00", Devise::Usd);
+        let promotion = promotion(Some("25"), None);
+        assert_eq!(promotion.remise(&sous_total).unwrap(), montant("20.00", Devise::Usd));
+    }
+
+    #[test]
+    fn remise_fixe_d_une_autre_devise_refusee() {
+        let sous_total = montant("80.00", Devise::Usd);
+        let promotion = promotion(None, Some(montant("5.00", Devise::Eur)));
+        assert!(matches!(promotion.remise(&sous_total), Err(MyError::BadRequest(_))));
+    }
+
+    #[test]
+    fn sous_total_des_lignes() {
+        let ligne = |prix: &str, quantite: i32| {
+            let prix_unitaire = montant(prix, Devise::Usd);
+            ArticleCommande {
+                id: Uuid::new_v4(),
+                commande_id: Uuid::nil(),
+                produit_id: Uuid::new_v4(),
+                variante_id: None,
+                nom_produit: "Produit".to_string(),
+                prix_unitaire,
+                quantite,
+                prix_total: prix_unitaire.fois(quantite).unwrap(),
+            }
+        };
+        let articles = [ligne("9.99", 2), ligne("0.02", 1)];
+        assert_eq!(ArticleCommande::sous_total(&articles, Devise::Usd).unwrap(), montant("20.00", Devise::Usd));
+        assert!(ArticleCommande::sous_total(&articles, Devise::Eur).is_err());
+    }
+}
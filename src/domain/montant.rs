@@ -0,0 +1,280 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::encode::IsNull;
+use sqlx::postgres::{PgArgumentBuffer, PgRow, PgTypeInfo};
+use sqlx::{Encode, Postgres, Row, Type};
+use validator::ValidationError;
+
+use crate::domain::error::MyError;
+
+// Plus grand montant des colonnes DECIMAL(12, 2) : 10 chiffres avant la virgule
+const CHIFFRES_ENTIERS_MAX: u32 = 10;
+
+// Colonne orders.devise (code ISO 4217)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Devise {
+    // Devise du catalogue : les colonnes de prix n'en portent pas
+    #[default]
+    Eur,
+    Usd,
+    Gbp,
+    Chf,
+    Xof,
+    Xaf,
+}
+
+impl Devise {
+    // Nombre de décimales de l'unité mineure
+    pub fn decimales(&self) -> u32 {
+        match self {
+            Devise::Xof | Devise::Xaf => 0,
+            _ => 2,
+        }
+    }
+}
+
+// Montant exact dans une devise ; jamais de flottant
+// JSON : { "valeur": "19.90", "devise": "EUR" }, devise du catalogue si absente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "MontantJson", into = "MontantJson")]
+pub struct Montant {
+    valeur: Decimal,
+    devise: Devise,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MontantJson {
+    valeur: Decimal,
+    #[serde(default)]
+    devise: Devise,
+}
+
+impl From<MontantJson> for Montant {
+    // Aucun arrondi ici : une précision excessive est refusée par la validation
+    fn from(json: MontantJson) -> Self {
+        Montant { valeur: json.valeur, devise: json.devise }
+    }
+}
+
+impl From<Montant> for MontantJson {
+    fn from(montant: Montant) -> Self {
+        let mut valeur = montant.valeur;
+        valeur.rescale(montant.devise.decimales());
+        MontantJson { valeur, devise: montant.devise }
+    }
+}
+
+impl Montant {
+    // Arrondi à l'unité mineure de la devise
+    pub fn new(valeur: Decimal, devise: Devise) -> Self {
+        Montant { valeur, devise }.arrondi()
+    }
+
+    pub fn zero(devise: Devise) -> Self {
+        Montant::new(Decimal::ZERO, devise)
+    }
+
+    pub fn valeur(&self) -> Decimal {
+        self.valeur
+    }
+
+    pub fn devise(&self) -> Devise {
+        self.devise
+    }
+
+    // Arrondi commercial : au plus proche, la demie s'éloignant de zéro (0.005 -> 0.01)
+    pub fn arrondi(self) -> Self {
+        let valeur = self
+            .valeur
+            .round_dp_with_strategy(self.devise.decimales(), RoundingStrategy::MidpointAwayFromZero);
+        Montant { valeur, devise: self.devise }
+    }
+
+    pub fn est_positif(&self) -> bool {
+        self.valeur > Decimal::ZERO
+    }
+
+    // Pas plus de décimales que l'unité mineure, et dans la capacité des colonnes
+    pub fn est_representable(&self) -> bool {
+        self.valeur.normalize().scale() <= self.devise.decimales()
+            && self.valeur.abs().trunc() < Decimal::from(10_i64.pow(CHIFFRES_ENTIERS_MAX))
+    }
+
+    fn meme_devise(&self, autre: &Montant) -> Result<(), MyError> {
+        if self.devise != autre.devise {
+            return Err(MyError::BadRequest(format!(
+                "Devises incompatibles: {:?} et {:?}",
+                self.devise, autre.devise
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn plus(&self, autre: &Montant) -> Result<Montant, MyError> {
+        self.meme_devise(autre)?;
+        let valeur = self.valeur.checked_add(autre.valeur).ok_or_else(depassement)?;
+        Ok(Montant::new(valeur, self.devise))
+    }
+
+    pub fn moins(&self, autre: &Montant) -> Result<Montant, MyError> {
+        self.meme_devise(autre)?;
+        let valeur = self.valeur.checked_sub(autre.valeur).ok_or_else(depassement)?;
+        Ok(Montant::new(valeur, self.devise))
+    }
+
+    // Prix d'une ligne : prix unitaire x quantité, exact
+    pub fn fois(&self, quantite: i32) -> Result<Montant, MyError> {
+        let valeur = self.valeur.checked_mul(Decimal::from(quantite)).ok_or_else(depassement)?;
+        Ok(Montant::new(valeur, self.devise))
+    }
+
+    // Part d'un montant (pourcentage de 0 à 100), arrondie une seule fois
+    pub fn pourcentage(&self, pourcentage: Decimal) -> Result<Montant, MyError> {
+        let valeur = self
+            .valeur
+            .checked_mul(pourcentage)
+            .and_then(|produit| produit.checked_div(Decimal::ONE_HUNDRED))
+            .ok_or_else(depassement)?;
+        Ok(Montant::new(valeur, self.devise))
+    }
+
+    pub fn min(self, autre: Montant) -> Result<Montant, MyError> {
+        self.meme_devise(&autre)?;
+        Ok(if autre.valeur < self.valeur { autre } else { self })
+    }
+
+    // Total d'une liste de montants dans une même devise
+    pub fn somme<'a>(montants: impl IntoIterator<Item = &'a Montant>, devise: Devise) -> Result<Montant, MyError> {
+        montants
+            .into_iter()
+            .try_fold(Montant::zero(devise), |total, montant| total.plus(montant))
+    }
+}
+
+fn depassement() -> MyError {
+    MyError::BadRequest("Montant hors limites".to_string())
+}
+
+// Deux montants de devises différentes ne se comparent pas
+impl PartialOrd for Montant {
+    fn partial_cmp(&self, autre: &Self) -> Option<Ordering> {
+        if self.devise != autre.devise {
+            return None;
+        }
+        self.valeur.partial_cmp(&autre.valeur)
+    }
+}
+
+// Colonnes DECIMAL : seule la valeur est stockée, la devise vient de la ligne (ou de sa commande)
+impl Type<Postgres> for Montant {
+    fn type_info() -> PgTypeInfo {
+        <Decimal as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Decimal as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Postgres> for Montant {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <Decimal as Encode<Postgres>>::encode_by_ref(&self.valeur, buf)
+    }
+}
+
+impl Montant {
+    // Pas de Decode : une colonne DECIMAL se relit toujours avec la devise qui l'accompagne
+    pub fn lire(row: &PgRow, colonne: &str, devise: Devise) -> Result<Montant, sqlx::Error> {
+        Ok(Montant { valeur: row.try_get(colonne)?, devise })
+    }
+
+    pub fn lire_option(row: &PgRow, colonne: &str, devise: Devise) -> Result<Option<Montant>, sqlx::Error> {
+        let valeur: Option<Decimal> = row.try_get(colonne)?;
+        Ok(valeur.map(|valeur| Montant { valeur, devise }))
+    }
+}
+
+fn refus(code: &'static str, message: &'static str) -> Result<(), ValidationError> {
+    let mut erreur = ValidationError::new(code);
+    erreur.message = Some(Cow::Borrowed(message));
+    Err(erreur)
+}
+
+// Montant saisi : devise du catalogue, précision de la devise
+pub fn montant_valide(montant: &Montant) -> Result<(), ValidationError> {
+    if montant.devise != Devise::default() {
+        return refus("devise", "Les montants du catalogue sont en EUR");
+    }
+    if !montant.est_representable() {
+        return refus("montant", "Montant invalide : deux décimales au plus, 10 chiffres avant la virgule");
+    }
+    if montant.valeur.is_sign_negative() {
+        return refus("montant_negatif", "Le montant ne peut pas être négatif");
+    }
+    Ok(())
+}
+
+// CHECK (prix > 0) de la table products
+pub fn prix_valide(prix: &Montant) -> Result<(), ValidationError> {
+    montant_valide(prix)?;
+    if !prix.est_positif() {
+        return refus("prix_nul", "Le prix doit être strictement positif");
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(valeur: &str) -> Decimal {
+        valeur.parse().unwrap()
+    }
+
+    #[test]
+    fn arrondi_demie_eloignee_de_zero() {
+        assert_eq!(Montant::new(dec("0.005"), Devise::Eur).valeur(), dec("0.01"));
+        assert_eq!(Montant::new(dec("0.004"), Devise::Eur).valeur(), dec("0.00"));
+        assert_eq!(Montant::new(dec("-0.005"), Devise::Eur).valeur(), dec("-0.01"));
+    }
+
+    #[test]
+    fn arrondi_sans_decimales_pour_le_franc_cfa() {
+        assert_eq!(Montant::new(dec("1234.5"), Devise::Xof).valeur(), dec("1235"));
+        assert_eq!(Montant::new(dec("1234.49"), Devise::Xaf).valeur(), dec("1234"));
+        assert!(!Montant { valeur: dec("10.5"), devise: Devise::Xof }.est_representable());
+    }
+
+    #[test]
+    fn pourcentage_arrondi_une_seule_fois() {
+        let prix = Montant::new(dec("19.99"), Devise::Eur);
+        assert_eq!(prix.pourcentage(dec("15")).unwrap().valeur(), dec("3.00"));
+        assert_eq!(prix.pourcentage(dec("100")).unwrap(), prix);
+        assert_eq!(prix.pourcentage(Decimal::ZERO).unwrap(), Montant::zero(Devise::Eur));
+    }
+
+    #[test]
+    fn devises_incompatibles() {
+        let euros = Montant::new(dec("10"), Devise::Eur);
+        let dollars = Montant::new(dec("10"), Devise::Usd);
+        assert!(matches!(euros.plus(&dollars), Err(MyError::BadRequest(_))));
+        assert!(matches!(euros.moins(&dollars), Err(MyError::BadRequest(_))));
+        assert!(matches!(euros.min(dollars), Err(MyError::BadRequest(_))));
+        assert!(Montant::somme([&euros, &dollars], Devise::Eur).is_err());
+        assert_eq!(euros.partial_cmp(&dollars), None);
+    }
+
+    #[test]
+    fn somme_et_multiplication_exactes() {
+        let prix = Montant::new(dec("0.10"), Devise::Eur);
+        let total = Montant::somme([&prix, &prix, &prix], Devise::Eur).unwrap();
+        assert_eq!(total.valeur(), dec("0.30"));
+        assert_eq!(prix.fois(3).unwrap(), total);
+    }
+}
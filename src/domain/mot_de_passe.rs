@@ -0,0 +1,126 @@
+use std::env;
+
+use std::borrow::Cow;
+
+use bcrypt::{HashParts, DEFAULT_COST};
+use validator::ValidationError;
+
+use crate::domain::error::MyError;
+
+// bcrypt ignore tout ce qui dépasse 72 octets
+const LONGUEUR_MIN: usize = 8;
+const LONGUEUR_MAX_OCTETS: usize = 72;
+// Bornes acceptées par bcrypt
+const COUT_MIN: u32 = 4;
+const COUT_MAX: u32 = 31;
+
+// Politique de robustesse, utilisée par les validateurs de CreateUser/UpdateUser
+pub fn politique_mot_de_passe(mot_de_passe: &str) -> Result<(), ValidationError> {
+    let refus = |code: &'static str, message: &'static str| {
+        let mut erreur = ValidationError::new(code);
+        erreur.message = Some(Cow::Borrowed(message));
+        Err(erreur)
+    };
+    if mot_de_passe.chars().count() < LONGUEUR_MIN {
+        return refus("mot_de_passe_trop_court", "Le mot de passe doit contenir au moins 8 caractères");
+    }
+    if mot_de_passe.len() > LONGUEUR_MAX_OCTETS {
+        return refus("mot_de_passe_trop_long", "Le mot de passe ne doit pas dépasser 72 octets");
+    }
+    if !mot_de_passe.chars().any(char::is_alphabetic) || !mot_de_passe.chars().any(|c| c.is_ascii_digit()) {
+        return refus("mot_de_passe_faible", "Le mot de passe doit contenir au moins une lettre et un chiffre");
+    }
+    Ok(())
+}
+
+// Service de hachage des mots de passe (bcrypt)
+#[derive(Debug, Clone)]
+pub struct HacheurMotDePasse {
+    cout: u32,
+    // Hash de référence au même coût, vérifié quand le compte n'existe pas
+    hache_factice: String,
+}
+
+impl HacheurMotDePasse {
+    pub fn new(cout: u32) -> Result<Self, MyError> {
+        if !(COUT_MIN..=COUT_MAX).contains(&cout) {
+            return Err(MyError::Custom(format!("BCRYPT_COST doit être compris entre {} et {}", COUT_MIN, COUT_MAX)));
+        }
+        let hache_factice = bcrypt::hash("mot de passe factice", cout)
+            .map_err(|e| MyError::Custom(format!("Hachage impossible: {}", e)))?;
+        Ok(Self { cout, hache_factice })
+    }
+
+    // Lit le coût dans BCRYPT_COST, sinon le coût par défaut de bcrypt
+    pub fn depuis_env() -> Result<Self, MyError> {
+        let cout = match env::var("BCRYPT_COST") {
+            Ok(valeur) => valeur
+                .trim()
+                .parse()
+                .map_err(|_| MyError::Custom(format!("BCRYPT_COST invalide: {}", valeur)))?,
+            Err(_) => DEFAULT_COST,
+        };
+        Self::new(cout)
+    }
+
+    pub async fn hacher(&self, mot_de_passe: &str) -> Result<String, MyError> {
+        if mot_de_passe.is_empty() {
+            return Err(MyError::Validation("Le mot de passe ne peut pas être vide".to_string()));
+        }
+        let mot_de_passe = mot_de_passe.to_string();
+        let cout = self.cout;
+        // bcrypt est coûteux en CPU : on ne bloque pas le worker actix
+        tokio::task::spawn_blocking(move || bcrypt::hash(mot_de_passe, cout))
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?
+            .map_err(|e| MyError::Custom(format!("Hachage impossible: {}", e)))
+    }
+
+    pub async fn verifier(&self, mot_de_passe: &str, hache: &str) -> Result<bool, MyError> {
+        let mot_de_passe = mot_de_passe.to_string();
+        let hache = hache.to_string();
+        tokio::task::spawn_blocking(move || bcrypt::verify(mot_de_passe, &hache))
+            .await
+            .map_err(|e| MyError::Custom(e.to_string()))?
+            .map_err(|e| MyError::Custom(format!("Vérification impossible: {}", e)))
+    }
+
+    // Même durée qu'une vraie vérification : le temps de réponse ne révèle pas si l'email existe
+    pub async fn verifier_factice(&self, mot_de_passe: &str) -> Result<(), MyError> {
+        self.verifier(mot_de_passe, &self.hache_factice).await?;
+        Ok(())
+    }
+
+    // Vrai si le hash a été produit avec un autre coût que celui configuré
+    pub fn doit_rehacher(&self, hache: &str) -> bool {
+        match hache.parse::<HashParts>() {
+            Ok(parts) => parts.get_cost() != self.cout,
+            Err(_) => true,
+        }
+    }
+
+    // Vérifie le mot de passe et renvoie un nouveau hash si le coût a changé,
+    // à enregistrer par l'appelant (rehachage transparent à la connexion)
+    pub async fn verifier_et_rehacher(&self, mot_de_passe: &str, hache: &str) -> Result<Option<String>, MyError> {
+        if !self.verifier(mot_de_passe, hache).await? {
+            return Err(MyError::Unauthorized("Identifiants invalides".to_string()));
+        }
+        if self.doit_rehacher(hache) {
+            return Ok(Some(self.hacher(mot_de_passe).await?));
+        }
+        Ok(None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cout_hors_bornes_refuse() {
+        assert!(matches!(HacheurMotDePasse::new(3), Err(MyError::Custom(_))));
+        assert!(matches!(HacheurMotDePasse::new(32), Err(MyError::Custom(_))));
+        assert!(HacheurMotDePasse::new(4).is_ok());
+    }
+}
@@ -0,0 +1,222 @@
+use std::env;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::jeton::hacher_jeton;
+use crate::domain::error::MyError;
+use crate::domain::mot_de_passe::HacheurMotDePasse;
+use crate::domain::user::{CreateUser, Utilisateur};
+
+// Délai pour revenir du fournisseur avec le code d'autorisation
+const DUREE_DEMANDE_MINUTES: i64 = 10;
+
+// Un fournisseur OpenID Connect (Google, Microsoft, émetteur de test local, ...)
+#[derive(Debug, Clone)]
+pub struct ConfigFournisseur {
+    pub nom: String,
+    // Découverte via {emetteur}/.well-known/openid-configuration
+    pub emetteur: String,
+    pub client_id: String,
+    // Absent pour un client public : PKCE seul
+    pub client_secret: Option<String>,
+    pub redirection: String,
+    pub portees: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOidc {
+    pub fournisseurs: Vec<ConfigFournisseur>,
+}
+
+impl ConfigOidc {
+    // OIDC_FOURNISSEURS=google,local puis, pour chacun, OIDC_<NOM>_EMETTEUR, _CLIENT_ID,
+    // _REDIRECTION et optionnellement _CLIENT_SECRET et _PORTEES ("openid email profile")
+    pub fn depuis_env() -> Self {
+        let liste = env::var("OIDC_FOURNISSEURS").unwrap_or_default();
+        let fournisseurs = liste
+            .split(',')
+            .map(|nom| nom.trim().to_ascii_lowercase())
+            .filter(|nom| !nom.is_empty())
+            .filter_map(|nom| {
+                let variable = |suffixe: &str| {
+                    env::var(format!("OIDC_{}_{}", nom.to_ascii_uppercase(), suffixe))
+                        .ok()
+                        .filter(|valeur| !valeur.trim().is_empty())
+                };
+                match (variable("EMETTEUR"), variable("CLIENT_ID"), variable("REDIRECTION")) {
+                    (Some(emetteur), Some(client_id), Some(redirection)) => Some(ConfigFournisseur {
+                        emetteur: emetteur.trim_end_matches('/').to_string(),
+                        client_id,
+                        client_secret: variable("CLIENT_SECRET"),
+                        redirection,
+                        portees: variable("PORTEES").unwrap_or_else(|| "openid email profile".to_string()),
+                        nom,
+                    }),
+                    _ => {
+                        tracing::warn!("Fournisseur OIDC {} incomplet, ignoré", nom);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { fournisseurs }
+    }
+
+    pub fn fournisseur(&self, nom: &str) -> Option<&ConfigFournisseur> {
+        self.fournisseurs.iter().find(|fournisseur| fournisseur.nom == nom)
+    }
+}
+
+fn aleatoire_base64(taille: usize) -> String {
+    let mut octets = vec![0u8; taille];
+    rand::thread_rng().fill_bytes(&mut octets);
+    URL_SAFE_NO_PAD.encode(octets)
+}
+
+// Table: demandes_oidc
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct DemandeOidc {
+    pub etat_hache: String,
+    pub fournisseur: String,
+    pub verificateur_pkce: String,
+    pub nonce: String,
+    pub date_expiration: DateTime<Utc>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl DemandeOidc {
+    // Renvoie le paramètre state en clair (transmis au fournisseur) et la demande stockée
+    pub fn new(fournisseur: &str) -> (String, Self) {
+        let etat = aleatoire_base64(32);
+        let now = Utc::now();
+        let demande = DemandeOidc {
+            etat_hache: hacher_jeton(&etat),
+            fournisseur: fournisseur.to_string(),
+            verificateur_pkce: aleatoire_base64(32),
+            nonce: aleatoire_base64(16),
+            date_expiration: now + Duration::minutes(DUREE_DEMANDE_MINUTES),
+            date_creation: now,
+        };
+        (etat, demande)
+    }
+
+    // code_challenge de la méthode S256 (RFC 7636)
+    pub fn defi_pkce(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.verificateur_pkce.as_bytes()))
+    }
+}
+
+// Table: identites_externes
+#[derive(Debug, Serialize, Clone, FromRow, PartialEq, Eq)]
+pub struct IdentiteExterne {
+    pub id: Uuid,
+    pub utilisateur_id: Uuid,
+    pub fournisseur: String,
+    pub sujet: String,
+    pub email: Option<String>,
+    pub date_derniere_connexion: DateTime<Utc>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl IdentiteExterne {
+    pub fn new(utilisateur_id: Uuid, fournisseur: &str, profil: &ProfilOidc) -> Self {
+        let now = Utc::now();
+        IdentiteExterne {
+            id: Uuid::new_v4(),
+            utilisateur_id,
+            fournisseur: fournisseur.to_string(),
+            sujet: profil.sujet.clone(),
+            email: profil.email.clone(),
+            date_derniere_connexion: now,
+            date_creation: now,
+        }
+    }
+}
+
+// Identité extraite d'un id_token validé (signature, émetteur, audience, nonce)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilOidc {
+    pub sujet: String,
+    pub email: Option<String>,
+    pub email_verifie: bool,
+    pub prenom: Option<String>,
+    pub nom: Option<String>,
+}
+
+impl ProfilOidc {
+    // Nom et prénom absents ou vides : partie locale de l'email, pour passer la validation
+    pub fn creation_compte(&self, email: String) -> Result<CreateUser, MyError> {
+        let local = email.split('@').next().unwrap_or_default().to_string();
+        let ou_local = |valeur: &Option<String>| {
+            let valeur = valeur.as_deref().map(str::trim).filter(|valeur| !valeur.is_empty()).unwrap_or(&local);
+            valeur.chars().take(100).collect()
+        };
+        let creation = CreateUser {
+            prenom: ou_local(&self.prenom),
+            nom: ou_local(&self.nom),
+            email,
+            // Le chiffre final satisfait la politique des mots de passe quel que soit le tirage
+            mot_de_passe: format!("{}0", aleatoire_base64(32)),
+        };
+        creation.validate()?;
+        Ok(creation)
+    }
+
+    // Compte créé à la première connexion : email déjà vérifié par le fournisseur,
+    // mot de passe aléatoire (un mot de passe local peut être défini via « mot de passe oublié »)
+    pub async fn nouveau_compte(&self, email: String, hacheur: &HacheurMotDePasse) -> Result<Utilisateur, MyError> {
+        let mut utilisateur = Utilisateur::new(self.creation_compte(email)?, hacheur).await?;
+        utilisateur.email_verifie = true;
+        Ok(utilisateur)
+    }
+}
+
+// Paramètres du retour du fournisseur sur l'URL de redirection
+#[derive(Debug, Deserialize, Clone)]
+pub struct RetourOidc {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profil(prenom: Option<&str>, nom: Option<&str>) -> ProfilOidc {
+        ProfilOidc {
+            sujet: "sujet".to_string(),
+            email: Some("jean.dupont@exemple.fr".to_string()),
+            email_verifie: true,
+            prenom: prenom.map(str::to_string),
+            nom: nom.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn noms_absents_remplaces_par_la_partie_locale() {
+        let creation = profil(None, Some("  ")).creation_compte("jean.dupont@exemple.fr".to_string()).unwrap();
+        assert_eq!(creation.prenom, "jean.dupont");
+        assert_eq!(creation.nom, "jean.dupont");
+        let creation = profil(Some("Jean"), Some("Dupont")).creation_compte("jd@exemple.fr".to_string()).unwrap();
+        assert_eq!((creation.prenom.as_str(), creation.nom.as_str()), ("Jean", "Dupont"));
+    }
+
+    #[test]
+    fn email_invalide_refuse() {
+        assert!(matches!(
+            profil(Some("Jean"), Some("Dupont")).creation_compte("pas-un-email".to_string()),
+            Err(MyError::ChampsInvalides(champs)) if champs[0].champ == "email"
+        ));
+    }
+}
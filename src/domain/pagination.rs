@@ -0,0 +1,113 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::error::MyError;
+
+pub const LIMITE_PAR_DEFAUT: u32 = 20;
+pub const LIMITE_MAX: u32 = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Ordre {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl Ordre {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            Ordre::Asc => "ASC",
+            Ordre::Desc => "DESC",
+        }
+    }
+}
+
+// Position opaque dans une liste triée : valeur de la colonne de tri + id
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Curseur {
+    pub valeur: String,
+    pub id: Uuid,
+}
+
+impl Curseur {
+    pub fn encoder(&self) -> String {
+        // La sérialisation d'une String et d'un Uuid ne peut pas échouer
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decoder(brut: &str) -> Result<Self, MyError> {
+        URL_SAFE_NO_PAD
+            .decode(brut)
+            .ok()
+            .and_then(|octets| serde_json::from_slice(&octets).ok())
+            .ok_or_else(|| MyError::BadRequest("Curseur invalide".to_string()))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Page<T> {
+    pub elements: Vec<T>,
+    pub total: i64,
+    pub limite: u32,
+    pub curseur_suivant: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            elements: self.elements.into_iter().map(f).collect(),
+            total: self.total,
+            limite: self.limite,
+            curseur_suivant: self.curseur_suivant,
+        }
+    }
+}
+
+pub fn limite_effective(limite: Option<u32>) -> u32 {
+    limite.unwrap_or(LIMITE_PAR_DEFAUT).clamp(1, LIMITE_MAX)
+}
+
+// Échappe les jokers LIKE d'un texte de recherche
+pub fn motif_recherche(recherche: &str) -> String {
+    let echappe = recherche
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", echappe)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curseur_aller_retour() {
+        let curseur = Curseur { valeur: "2026-10-18T09:00:00.000000Z".to_string(), id: Uuid::new_v4() };
+        let encode = curseur.encoder();
+        assert!(encode.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Curseur::decoder(&encode).unwrap(), curseur);
+    }
+
+    #[test]
+    fn curseur_invalide_refuse() {
+        assert!(matches!(Curseur::decoder("pas un curseur!"), Err(MyError::BadRequest(_))));
+        assert!(Curseur::decoder(&URL_SAFE_NO_PAD.encode(b"{\"valeur\":1}")).is_err());
+    }
+
+    #[test]
+    fn limite_bornee() {
+        assert_eq!(limite_effective(None), LIMITE_PAR_DEFAUT);
+        assert_eq!(limite_effective(Some(0)), 1);
+        assert_eq!(limite_effective(Some(1000)), LIMITE_MAX);
+    }
+
+    #[test]
+    fn jokers_echappes() {
+        assert_eq!(motif_recherche(" 50%_a\\b "), "%50\\%\\_a\\\\b%");
+    }
+}
@@ -0,0 +1,162 @@
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::models::Produit;
+use crate::domain::montant::{prix_valide, Montant};
+use crate::domain::pagination::Ordre;
+
+
+// Longueurs alignées sur les VARCHAR de la table products
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreationProduit {
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
+    pub nom: String,
+    pub description: String,
+    #[validate(length(min = 1, max = 50, message = "Entre 1 et 50 caractères"))]
+    pub reference: String,
+    #[validate(custom = "prix_valide")]
+    pub prix: Montant,
+    #[validate(range(min = 0, message = "Quantité négative"))]
+    #[serde(default)]
+    pub quantite: i32,
+    pub categorie_id: Option<Uuid>,
+    #[validate(url(message = "URL invalide"), length(max = 255, message = "255 caractères maximum"))]
+    pub image_principale_url: Option<String>,
+    // Publié par défaut, comme dans la table
+    pub est_publie: Option<bool>,
+}
+
+// Remplacement complet des champs modifiables (PUT)
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct MiseAJourProduit {
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
+    pub nom: String,
+    pub description: String,
+    #[validate(length(min = 1, max = 50, message = "Entre 1 et 50 caractères"))]
+    pub reference: String,
+    #[validate(custom = "prix_valide")]
+    pub prix: Montant,
+    #[validate(range(min = 0, message = "Quantité négative"))]
+    pub quantite: i32,
+    pub categorie_id: Option<Uuid>,
+    #[validate(url(message = "URL invalide"), length(max = 255, message = "255 caractères maximum"))]
+    pub image_principale_url: Option<String>,
+    pub est_publie: bool,
+}
+
+impl CreationProduit {
+    // À appeler avant la validation : la référence sans espaces autour est celle qui est validée et stockée
+    pub fn normalisee(mut self) -> Self {
+        self.reference = self.reference.trim().to_string();
+        self
+    }
+}
+
+impl MiseAJourProduit {
+    pub fn normalisee(mut self) -> Self {
+        self.reference = self.reference.trim().to_string();
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TriProduits {
+    #[default]
+    DateCreation,
+    Nom,
+    Prix,
+}
+
+impl TriProduits {
+    pub fn colonne(&self) -> &'static str {
+        match self {
+            TriProduits::DateCreation => "date_creation",
+            TriProduits::Nom => "nom",
+            TriProduits::Prix => "prix",
+        }
+    }
+}
+
+// Paramètres de GET /produits : page/limite ou curseur, tri et filtres
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RequeteProduits {
+    pub page: Option<u32>,
+    pub limite: Option<u32>,
+    pub curseur: Option<String>,
+    #[serde(default)]
+    pub tri: TriProduits,
+    #[serde(default)]
+    pub ordre: Ordre,
+    pub categorie_id: Option<Uuid>,
+    // Avec categorie_id : produits de la catégorie et de toutes ses descendantes
+    #[serde(default)]
+    pub sous_categories: bool,
+    // Bornes incluses, dans la devise du catalogue
+    pub prix_min: Option<Decimal>,
+    pub prix_max: Option<Decimal>,
+    // Recherche sur nom et référence
+    pub recherche: Option<String>,
+    // Forcé à true sans Permission::GererCatalogue
+    pub publie: Option<bool>,
+}
+
+impl Produit {
+    pub fn new(creation: CreationProduit) -> Self {
+        Produit {
+            id: Uuid::new_v4(),
+            nom: creation.nom,
+            description: creation.description,
+            reference: creation.reference,
+            prix: creation.prix,
+            quantite: creation.quantite,
+            categorie_id: creation.categorie_id,
+            image_principale_url: creation.image_principale_url,
+            est_publie: creation.est_publie.unwrap_or(true),
+            date_creation: Utc::now(),
+        }
+    }
+
+    pub fn remplacer(&mut self, mise_a_jour: MiseAJourProduit) {
+        self.nom = mise_a_jour.nom;
+        self.description = mise_a_jour.description;
+        self.reference = mise_a_jour.reference;
+        self.prix = mise_a_jour.prix;
+        self.quantite = mise_a_jour.quantite;
+        self.categorie_id = mise_a_jour.categorie_id;
+        self.image_principale_url = mise_a_jour.image_principale_url;
+        self.est_publie = mise_a_jour.est_publie;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::montant::Devise;
+
+    fn creation(reference: &str) -> CreationProduit {
+        CreationProduit {
+            nom: "Tasse".to_string(),
+            description: String::new(),
+            reference: reference.to_string(),
+            prix: Montant::new(Decimal::new(1250, 2), Devise::Eur),
+            quantite: 3,
+            categorie_id: None,
+            image_principale_url: None,
+            est_publie: None,
+        }
+    }
+
+    #[test]
+    fn reference_validee_sans_espaces() {
+        assert!(creation("   ").normalisee().validate().is_err());
+        let creation = creation(&format!("  {}  ", "R".repeat(50))).normalisee();
+        assert!(creation.validate().is_ok());
+        assert_eq!(Produit::new(creation).reference, "R".repeat(50));
+    }
+}
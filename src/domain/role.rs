@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+// Colonne utilisateur.role
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Client,
+    Personnel,
+    Admin,
+    // Compte technique (ERP, entrepôt), authentifié par clé d'API uniquement
+    Service,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    // Lire le compte d'un autre utilisateur
+    LireUtilisateurs,
+    ListerUtilisateurs,
+    ModifierUtilisateurs,
+    SupprimerUtilisateurs,
+    GererRoles,
+    // Champs internes (date_update, ...) dans les réponses
+    VoirDetailsComptes,
+    // Archive RGPD du compte d'un autre utilisateur
+    ExporterDonnees,
+    // Lister et restaurer les comptes supprimés
+    GererSuppressions,
+    // Consulter le journal d'audit
+    LireAudit,
+    // Agir en tant qu'un client, pour le support
+    UsurperIdentite,
+    // Émettre et révoquer les clés d'API des comptes de service
+    GererClesApi,
+    // Créer, modifier et supprimer les produits ; voir les produits non publiés
+    GererCatalogue,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Client => &[],
+            Role::Personnel => &[Permission::LireUtilisateurs, Permission::GererCatalogue],
+            Role::Admin => &[
+                Permission::LireUtilisateurs,
+                Permission::ListerUtilisateurs,
+                Permission::ModifierUtilisateurs,
+                Permission::SupprimerUtilisateurs,
+                Permission::GererRoles,
+                Permission::VoirDetailsComptes,
+                Permission::ExporterDonnees,
+                Permission::GererSuppressions,
+                Permission::LireAudit,
+                Permission::UsurperIdentite,
+                Permission::GererClesApi,
+                Permission::GererCatalogue,
+            ],
+            // Plafond des portées d'une clé d'API
+            Role::Service => &[Permission::LireUtilisateurs, Permission::ListerUtilisateurs, Permission::GererCatalogue],
+        }
+    }
+
+    pub fn a_permission(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangerRole {
+    pub role: Role,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Permission::*;
+
+    const TOUTES: [Permission; 12] = [
+        LireUtilisateurs,
+        ListerUtilisateurs,
+        ModifierUtilisateurs,
+        SupprimerUtilisateurs,
+        GererRoles,
+        VoirDetailsComptes,
+        ExporterDonnees,
+        GererSuppressions,
+        LireAudit,
+        UsurperIdentite,
+        GererClesApi,
+        GererCatalogue,
+    ];
+
+    // Ne compile plus si une permission est ajoutée sans être rangée dans TOUTES
+    #[allow(dead_code)]
+    fn repertoriee(permission: Permission) {
+        match permission {
+            LireUtilisateurs | ListerUtilisateurs | ModifierUtilisateurs | SupprimerUtilisateurs | GererRoles
+            | VoirDetailsComptes | ExporterDonnees | GererSuppressions | LireAudit | UsurperIdentite
+            | GererClesApi | GererCatalogue => {}
+        }
+    }
+
+    #[test]
+    fn permissions_par_role() {
+        let attendues: [(Role, &[Permission]); 4] = [
+            (Role::Client, &[]),
+            (Role::Personnel, &[LireUtilisateurs, GererCatalogue]),
+            (Role::Admin, &TOUTES),
+            (Role::Service, &[LireUtilisateurs, ListerUtilisateurs, GererCatalogue]),
+        ];
+        for (role, permissions) in attendues {
+            for permission in TOUTES {
+                assert_eq!(
+                    role.a_permission(permission),
+                    permissions.contains(&permission),
+                    "{:?} / {:?}",
+                    role,
+                    permission,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn service_sans_gestion_des_comptes() {
+        // Une clé d'API volée ne doit ni modifier les comptes ni élever ses propres droits
+        for permission in [
+            ModifierUtilisateurs,
+            SupprimerUtilisateurs,
+            GererRoles,
+            VoirDetailsComptes,
+            ExporterDonnees,
+            GererSuppressions,
+            LireAudit,
+            UsurperIdentite,
+            GererClesApi,
+        ] {
+            assert!(!Role::Service.a_permission(permission), "{:?}", permission);
+        }
+    }
+}
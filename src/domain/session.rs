@@ -0,0 +1,133 @@
+use std::env;
+
+use chrono::{DateTime, Duration, Utc};
+use rand::distributions::Alphanumeric;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::domain::models::Session;
+use crate::domain::jeton::hacher_jeton;
+
+const LONGUEUR_TOKEN: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct ConfigSession {
+    pub duree: Duration,
+    // Durée de vie d'une session d'appareil, repoussée à chaque rafraîchissement
+    pub duree_rafraichissement: Duration,
+    pub intervalle_balayage: std::time::Duration,
+}
+
+impl ConfigSession {
+    // SESSION_DUREE_SECS (7 jours par défaut), JWT_RAFRAICHISSEMENT_DUREE_SECS (30 jours)
+    // et SESSION_BALAYAGE_SECS (1 heure)
+    pub fn depuis_env() -> Self {
+        let secondes = |cle: &str, defaut: u64| {
+            env::var(cle)
+                .ok()
+                .and_then(|valeur| valeur.parse().ok())
+                .unwrap_or(defaut)
+        };
+        Self {
+            duree: Duration::seconds(secondes("SESSION_DUREE_SECS", 7 * 24 * 3600) as i64),
+            duree_rafraichissement: Duration::seconds(secondes("JWT_RAFRAICHISSEMENT_DUREE_SECS", 30 * 24 * 3600) as i64),
+            intervalle_balayage: std::time::Duration::from_secs(secondes("SESSION_BALAYAGE_SECS", 3600)),
+        }
+    }
+}
+
+impl Session {
+    // Session invitée (utilisateur_id = None) ou rattachée à un compte
+    pub fn new(utilisateur_id: Option<Uuid>, duree: Duration) -> Self {
+        let now = Utc::now();
+        let token = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(LONGUEUR_TOKEN)
+            .map(char::from)
+            .collect();
+        Session {
+            id: Uuid::new_v4(),
+            token,
+            utilisateur_id,
+            date_expiration: now + duree,
+            date_creation: now,
+            appareil: None,
+            adresse_ip: None,
+            derniere_activite: now,
+        }
+    }
+
+    // Session ouverte par une connexion, identifiée auprès de l'utilisateur par son appareil
+    pub fn appareil(utilisateur_id: Uuid, duree: Duration, appareil: Option<String>, adresse_ip: Option<String>) -> Self {
+        Session {
+            appareil,
+            adresse_ip,
+            ..Session::new(Some(utilisateur_id), duree)
+        }
+    }
+}
+
+// Table: jetons_rafraichissement
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct JetonRafraichissement {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub jeton_hache: String,
+    pub date_utilisation: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl JetonRafraichissement {
+    // Renvoie le jeton en clair (remis au client) et sa version stockée
+    pub fn new(session_id: Uuid) -> (String, Self) {
+        let mut octets = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut octets);
+        let clair = hex::encode(octets);
+        let jeton = JetonRafraichissement {
+            id: Uuid::new_v4(),
+            session_id,
+            jeton_hache: hacher_jeton(&clair),
+            date_utilisation: None,
+            date_creation: Utc::now(),
+        };
+        (clair, jeton)
+    }
+}
+
+// Issue de la présentation d'un jeton de rafraîchissement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rotation {
+    // Jeton courant : consommé, la session peut en recevoir un nouveau
+    Valide(Session),
+    // Jeton déjà échangé : probablement volé, toute la session doit être révoquée
+    Reutilise(Session),
+    Inconnu,
+}
+
+// Vue d'une session d'appareil pour son propriétaire (sans le token)
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionActive {
+    pub id: Uuid,
+    pub appareil: Option<String>,
+    pub adresse_ip: Option<String>,
+    pub derniere_activite: DateTime<Utc>,
+    pub date_creation: DateTime<Utc>,
+    pub date_expiration: DateTime<Utc>,
+    pub courante: bool,
+}
+
+impl SessionActive {
+    pub fn depuis(session: &Session, courante: Option<Uuid>) -> Self {
+        SessionActive {
+            id: session.id,
+            appareil: session.appareil.clone(),
+            adresse_ip: session.adresse_ip.clone(),
+            derniere_activite: session.derniere_activite,
+            date_creation: session.date_creation,
+            date_expiration: session.date_expiration,
+            courante: courante == Some(session.id),
+        }
+    }
+}
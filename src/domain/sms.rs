@@ -0,0 +1,127 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::domain::error::MyError;
+use crate::domain::jeton::hacher_jeton;
+
+// Au-delà, le code est invalidé et doit être redemandé
+pub const ESSAIS_MAX_CODE_SMS: i32 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sms {
+    pub destinataire: String,
+    pub texte: String,
+}
+
+impl Sms {
+    pub fn code_connexion(destinataire: &str, code: &str, validite: Duration) -> Self {
+        Sms {
+            destinataire: destinataire.to_string(),
+            texte: format!(
+                "Votre code de connexion Boutique : {} (valable {} minutes). Ne le communiquez à personne.",
+                code,
+                validite.num_minutes()
+            ),
+        }
+    }
+
+    pub fn verification_telephone(destinataire: &str, code: &str, validite: Duration) -> Self {
+        Sms {
+            destinataire: destinataire.to_string(),
+            texte: format!(
+                "Code de vérification de votre numéro sur Boutique : {} (valable {} minutes).",
+                code,
+                validite.num_minutes()
+            ),
+        }
+    }
+}
+
+// Forme E.164 : "+", indicatif et numéro, sans séparateurs
+pub fn normaliser_telephone(brut: &str) -> Result<String, MyError> {
+    let numero: String = brut
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '.' | '-' | '(' | ')'))
+        .collect();
+    let chiffres = numero.strip_prefix('+').unwrap_or_default();
+    if !(8..=15).contains(&chiffres.len()) || !chiffres.chars().all(|c| c.is_ascii_digit()) || chiffres.starts_with('0') {
+        return Err(MyError::BadRequest("Numéro de téléphone invalide, format international attendu (+33...)".to_string()));
+    }
+    Ok(numero)
+}
+
+// Table: telephones
+#[derive(Debug, Serialize, Clone, FromRow, PartialEq, Eq)]
+pub struct Telephone {
+    pub utilisateur_id: Uuid,
+    pub numero: String,
+    pub date_verification: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+// Colonne codes_sms.objet
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ObjetCodeSms {
+    Connexion,
+    VerificationTelephone,
+}
+
+// Table: codes_sms
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct CodeSms {
+    pub id: Uuid,
+    pub utilisateur_id: Uuid,
+    pub objet: ObjetCodeSms,
+    pub telephone: String,
+    pub code_hache: String,
+    pub essais: i32,
+    pub date_expiration: DateTime<Utc>,
+    pub date_utilisation: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl CodeSms {
+    // Renvoie le code en clair (à envoyer par SMS) et sa version stockée
+    pub fn new(utilisateur_id: Uuid, objet: ObjetCodeSms, telephone: &str, duree: Duration) -> (String, Self) {
+        let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+        let now = Utc::now();
+        let stocke = CodeSms {
+            id: Uuid::new_v4(),
+            utilisateur_id,
+            objet,
+            telephone: telephone.to_string(),
+            code_hache: hacher_jeton(&code),
+            essais: 0,
+            date_expiration: now + duree,
+            date_utilisation: None,
+            date_creation: now,
+        };
+        (code, stocke)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangementTelephone {
+    pub telephone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfirmationTelephone {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandeCodeSms {
+    pub telephone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnexionSms {
+    pub telephone: String,
+    pub code: String,
+}
@@ -0,0 +1,172 @@
+use std::env;
+use std::net::IpAddr;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::domain::error::MyError;
+use crate::domain::email::ReglesEmail;
+
+// Table: tentatives_connexion
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct EtatTentatives {
+    pub cle: String,
+    pub echecs: i32,
+    pub bloque_jusqu_a: Option<DateTime<Utc>>,
+    pub derniere_tentative: DateTime<Utc>,
+}
+
+// Les variantes d'une même adresse partagent le compteur du compte
+pub fn cle_compte(email: &str, regles: &ReglesEmail) -> String {
+    format!("compte:{}", regles.cle(email))
+}
+
+pub fn cle_ip(ip: IpAddr) -> String {
+    format!("ip:{}", ip)
+}
+
+// Envois de liens et de codes vers une adresse ou un numéro : chaque envoi compte comme un échec,
+// ce qui espace les envois successifs sans jamais verrouiller de compte
+pub fn cle_envoi(destination: &str) -> String {
+    format!("envoi:{}", destination.to_lowercase())
+}
+
+// Codes de vérification d'un nouveau numéro : délai croissant, sans verrouillage du compte
+pub fn cle_verification_telephone(utilisateur_id: Uuid) -> String {
+    format!("telephone:{}", utilisateur_id)
+}
+
+pub fn est_cle_compte(cle: &str) -> bool {
+    cle.starts_with("compte:")
+}
+
+#[derive(Debug, Clone)]
+pub struct PolitiqueTentatives {
+    // Échecs tolérés avant que le délai ne s'applique
+    pub echecs_sans_delai: i32,
+    pub delai_initial: Duration,
+    pub delai_max: Duration,
+    // Échecs sur un compte avant verrouillage temporaire
+    pub seuil_verrouillage: i32,
+    pub duree_verrouillage: Duration,
+    // Sans nouvel échec pendant cette durée, le compteur repart de zéro
+    pub fenetre_oubli: Duration,
+}
+
+impl PolitiqueTentatives {
+    pub fn depuis_env() -> Self {
+        let entier = |cle: &str, defaut: i64| {
+            env::var(cle)
+                .ok()
+                .and_then(|valeur| valeur.parse().ok())
+                .unwrap_or(defaut)
+        };
+        Self {
+            echecs_sans_delai: entier("CONNEXION_ECHECS_SANS_DELAI", 3) as i32,
+            delai_initial: Duration::seconds(entier("CONNEXION_DELAI_INITIAL_SECS", 2)),
+            delai_max: Duration::seconds(entier("CONNEXION_DELAI_MAX_SECS", 15 * 60)),
+            seuil_verrouillage: entier("CONNEXION_SEUIL_VERROUILLAGE", 10) as i32,
+            duree_verrouillage: Duration::seconds(entier("CONNEXION_DUREE_VERROUILLAGE_SECS", 30 * 60)),
+            fenetre_oubli: Duration::seconds(entier("CONNEXION_FENETRE_OUBLI_SECS", 24 * 3600)),
+        }
+    }
+
+    // Fin du blocage après `echecs` échecs consécutifs : délai doublé à chaque échec,
+    // verrouillage du compte au-delà du seuil
+    pub fn blocage_apres(&self, echecs: i32, est_compte: bool, maintenant: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if est_compte && echecs >= self.seuil_verrouillage {
+            return Some(maintenant + self.duree_verrouillage);
+        }
+        if echecs <= self.echecs_sans_delai {
+            return None;
+        }
+        let exposant = (echecs - self.echecs_sans_delai - 1).min(20) as u32;
+        let delai = self.delai_initial.num_seconds().saturating_mul(1 << exposant);
+        Some(maintenant + Duration::seconds(delai.min(self.delai_max.num_seconds())))
+    }
+
+    // Refuse la tentative si la clé est encore bloquée
+    pub fn verifier(&self, etat: &EtatTentatives, maintenant: DateTime<Utc>) -> Result<(), MyError> {
+        let Some(jusqu_a) = etat.bloque_jusqu_a.filter(|jusqu_a| *jusqu_a > maintenant) else {
+            return Ok(());
+        };
+        let reessayer_dans = (jusqu_a - maintenant).num_seconds().max(1);
+        if est_cle_compte(&etat.cle) && etat.echecs >= self.seuil_verrouillage {
+            Err(MyError::CompteVerrouille {
+                message: "Compte temporairement verrouillé".to_string(),
+                reessayer_dans,
+            })
+        } else {
+            Err(MyError::TropDeTentatives {
+                message: "Trop de tentatives de connexion".to_string(),
+                reessayer_dans,
+            })
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn politique() -> PolitiqueTentatives {
+        PolitiqueTentatives {
+            echecs_sans_delai: 3,
+            delai_initial: Duration::seconds(2),
+            delai_max: Duration::seconds(60),
+            seuil_verrouillage: 10,
+            duree_verrouillage: Duration::minutes(30),
+            fenetre_oubli: Duration::hours(24),
+        }
+    }
+
+    fn delai(echecs: i32, est_compte: bool) -> Option<i64> {
+        let maintenant = Utc::now();
+        politique().blocage_apres(echecs, est_compte, maintenant).map(|jusqu_a| (jusqu_a - maintenant).num_seconds())
+    }
+
+    #[test]
+    fn aucun_delai_sous_le_seuil() {
+        assert_eq!(delai(1, true), None);
+        assert_eq!(delai(3, true), None);
+    }
+
+    #[test]
+    fn delai_double_puis_plafonne() {
+        assert_eq!(delai(4, true), Some(2));
+        assert_eq!(delai(5, true), Some(4));
+        assert_eq!(delai(7, true), Some(16));
+        assert_eq!(delai(9, false), Some(60));
+        assert_eq!(delai(500, false), Some(60));
+    }
+
+    #[test]
+    fn verrouillage_du_compte_seulement() {
+        assert_eq!(delai(10, true), Some(30 * 60));
+        assert_eq!(delai(10, false), Some(60));
+    }
+
+    #[test]
+    fn verification_selon_l_etat() {
+        let maintenant = Utc::now();
+        let etat = |cle: &str, echecs, bloque_jusqu_a| EtatTentatives {
+            cle: cle.to_string(),
+            echecs,
+            bloque_jusqu_a,
+            derniere_tentative: maintenant,
+        };
+        let politique = politique();
+        assert!(politique.verifier(&etat("ip:127.0.0.1", 5, None), maintenant).is_ok());
+        assert!(politique.verifier(&etat("ip:127.0.0.1", 5, Some(maintenant - Duration::seconds(1))), maintenant).is_ok());
+        assert!(matches!(
+            politique.verifier(&etat("ip:127.0.0.1", 5, Some(maintenant + Duration::seconds(4))), maintenant),
+            Err(MyError::TropDeTentatives { reessayer_dans: 4, .. })
+        ));
+        assert!(matches!(
+            politique.verifier(&etat("compte:a@b.fr", 10, Some(maintenant + Duration::minutes(30))), maintenant),
+            Err(MyError::CompteVerrouille { reessayer_dans: 1800, .. })
+        ));
+    }
+}
@@ -0,0 +1,279 @@
+use std::env;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+const ALPHABET_BASE32: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const TAILLE_SECRET: usize = 20;
+const NOMBRE_CODES_RECUPERATION: usize = 10;
+
+// Table: double_facteur
+#[derive(Debug, Clone, FromRow, PartialEq, Eq)]
+pub struct DoubleFacteur {
+    pub utilisateur_id: Uuid,
+    pub secret: String,
+    pub actif: bool,
+    pub dernier_pas: Option<i64>,
+    pub date_activation: Option<DateTime<Utc>>,
+    pub date_creation: DateTime<Utc>,
+}
+
+impl DoubleFacteur {
+    // Secret aléatoire, inactif tant que l'utilisateur n'a pas saisi un premier code
+    pub fn new(utilisateur_id: Uuid) -> Self {
+        let mut secret = [0u8; TAILLE_SECRET];
+        rand::thread_rng().fill_bytes(&mut secret);
+        DoubleFacteur {
+            utilisateur_id,
+            secret: hex::encode(secret),
+            actif: false,
+            dernier_pas: None,
+            date_activation: None,
+            date_creation: Utc::now(),
+        }
+    }
+
+    fn octets_secret(&self) -> Vec<u8> {
+        hex::decode(&self.secret).unwrap_or_default()
+    }
+
+    pub fn secret_base32(&self) -> String {
+        base32(&self.octets_secret())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigTotp {
+    pub emetteur: String,
+    pub periode: i64,
+    pub chiffres: u32,
+    // Pas de temps acceptés de part et d'autre (décalage d'horloge)
+    pub tolerance: i64,
+}
+
+impl ConfigTotp {
+    pub fn depuis_env() -> Self {
+        Self {
+            emetteur: env::var("TOTP_EMETTEUR").unwrap_or_else(|_| "Boutique".to_string()),
+            periode: 30,
+            chiffres: 6,
+            tolerance: 1,
+        }
+    }
+
+    pub fn pas(&self, instant: DateTime<Utc>) -> i64 {
+        instant.timestamp().div_euclid(self.periode)
+    }
+
+    // URI otpauth:// à afficher en QR code dans l'application d'authentification
+    pub fn uri(&self, facteur: &DoubleFacteur, compte: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            encoder_uri(&self.emetteur),
+            encoder_uri(compte),
+            facteur.secret_base32(),
+            encoder_uri(&self.emetteur),
+            self.chiffres,
+            self.periode,
+        )
+    }
+
+    // Renvoie le pas de temps correspondant au code, s'il est valide et pas déjà utilisé
+    pub fn verifier(&self, facteur: &DoubleFacteur, code: &str, instant: DateTime<Utc>) -> Option<i64> {
+        let code = code.trim();
+        if code.len() != self.chiffres as usize {
+            return None;
+        }
+        let secret = facteur.octets_secret();
+        let courant = self.pas(instant);
+        (courant - self.tolerance..=courant + self.tolerance)
+            .filter(|pas| facteur.dernier_pas.is_none_or(|dernier| *pas > dernier))
+            .find(|pas| egal_temps_constant(&hotp(&secret, *pas as u64, self.chiffres), code))
+    }
+}
+
+// RFC 4226 : HMAC-SHA1 du compteur, troncature dynamique
+fn hotp(secret: &[u8], compteur: u64, chiffres: u32) -> String {
+    let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(secret).expect("HMAC accepte une clé de toute taille");
+    mac.update(&compteur.to_be_bytes());
+    let empreinte = mac.finalize().into_bytes();
+    let decalage = (empreinte[empreinte.len() - 1] & 0x0f) as usize;
+    let binaire = u32::from_be_bytes([
+        empreinte[decalage] & 0x7f,
+        empreinte[decalage + 1],
+        empreinte[decalage + 2],
+        empreinte[decalage + 3],
+    ]);
+    format!("{:0largeur$}", binaire % 10u32.pow(chiffres), largeur = chiffres as usize)
+}
+
+fn egal_temps_constant(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// RFC 4648 sans remplissage, format attendu par les applications TOTP
+fn base32(octets: &[u8]) -> String {
+    let mut sortie = String::with_capacity(octets.len().div_ceil(5) * 8);
+    let mut tampon: u32 = 0;
+    let mut bits = 0;
+    for octet in octets {
+        tampon = (tampon << 8) | u32::from(*octet);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            sortie.push(ALPHABET_BASE32[((tampon >> bits) & 0x1f) as usize] as char);
+        }
+        tampon &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        sortie.push(ALPHABET_BASE32[((tampon << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    sortie
+}
+
+fn encoder_uri(texte: &str) -> String {
+    texte
+        .bytes()
+        .map(|octet| match octet {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (octet as char).to_string(),
+            _ => format!("%{:02X}", octet),
+        })
+        .collect()
+}
+
+// Codes au format XXXXX-XXXXX, affichés une seule fois à l'utilisateur
+pub fn generer_codes_recuperation() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..NOMBRE_CODES_RECUPERATION)
+        .map(|_| {
+            let code: String = (0..10)
+                .map(|_| ALPHABET_BASE32[rng.gen_range(0..ALPHABET_BASE32.len())] as char)
+                .collect();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect()
+}
+
+// Forme canonique d'un code de récupération saisi (casse, tirets, espaces)
+pub fn normaliser_code_recuperation(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InscriptionDoubleFacteur {
+    pub secret: String,
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeDoubleFacteur {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VerificationDoubleFacteur {
+    pub jeton_deux_facteurs: String,
+    // Code TOTP ou code de récupération
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodesRecuperation {
+    pub codes_recuperation: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeuxFacteursRequis {
+    pub deux_facteurs_requis: bool,
+    pub jeton_deux_facteurs: String,
+    pub expire_dans: i64,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Secret des vecteurs de test des RFC 4226 et 6238 (SHA-1)
+    const SECRET_RFC: &[u8] = b"12345678901234567890";
+
+    fn facteur() -> DoubleFacteur {
+        DoubleFacteur {
+            utilisateur_id: Uuid::nil(),
+            secret: hex::encode(SECRET_RFC),
+            actif: true,
+            dernier_pas: None,
+            date_activation: None,
+            date_creation: Utc::now(),
+        }
+    }
+
+    fn config(chiffres: u32) -> ConfigTotp {
+        ConfigTotp { emetteur: "Boutique".to_string(), periode: 30, chiffres, tolerance: 1 }
+    }
+
+    fn instant(secondes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secondes, 0).unwrap()
+    }
+
+    #[test]
+    fn vecteurs_rfc_4226() {
+        let attendus = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        for (compteur, attendu) in attendus.iter().enumerate() {
+            assert_eq!(hotp(SECRET_RFC, compteur as u64, 6), *attendu);
+        }
+    }
+
+    #[test]
+    fn vecteurs_rfc_6238_sha1() {
+        let vecteurs = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+            (20000000000, "65353130"),
+        ];
+        let config = config(8);
+        for (secondes, attendu) in vecteurs {
+            let pas = config.pas(instant(secondes));
+            assert_eq!(hotp(SECRET_RFC, pas as u64, 8), attendu);
+            assert_eq!(config.verifier(&facteur(), attendu, instant(secondes)), Some(pas));
+        }
+    }
+
+    #[test]
+    fn tolerance_et_anti_rejeu() {
+        let config = config(8);
+        let mut facteur = facteur();
+        // Code du pas précédent, accepté avec une tolérance d'un pas
+        assert_eq!(config.verifier(&facteur, "94287082", instant(59 + 30)), Some(1));
+        assert_eq!(config.verifier(&facteur, "94287082", instant(59 + 90)), None);
+        facteur.dernier_pas = Some(1);
+        assert_eq!(config.verifier(&facteur, "94287082", instant(59)), None);
+        assert_eq!(config.verifier(&facteur, "9428708", instant(59)), None);
+    }
+
+    #[test]
+    fn secret_en_base32() {
+        assert_eq!(facteur().secret_base32(), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(base32(b"f"), "MY");
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn codes_de_recuperation() {
+        let codes = generer_codes_recuperation();
+        assert_eq!(codes.len(), NOMBRE_CODES_RECUPERATION);
+        assert!(codes.iter().all(|code| code.len() == 11 && code.as_bytes()[5] == b'-'));
+        assert_eq!(normaliser_code_recuperation(" abcde-fgh23 "), "ABCDEFGH23");
+    }
+}
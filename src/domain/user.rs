@@ -1,52 +1,368 @@
 use chrono::{DateTime, Utc};
+use json_patch::Patch;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use sqlx::FromRow;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::error::{ChampInvalide, MyError};
+use crate::domain::mot_de_passe::{politique_mot_de_passe, HacheurMotDePasse};
+use crate::domain::role::{Permission, Role};
+use crate::domain::pagination::Ordre;
+use crate::domain::concurrence;
+use crate::domain::email::normaliser_email;
 
 
 // Table: utilisateurs
-#[derive(Debug, Serialize, Deserialize, Clone, FromRow, PartialEq, Eq)]
+// Pas de Serialize : les réponses passent par les vues ci-dessous
+#[derive(Debug, Deserialize, Clone, FromRow, PartialEq, Eq)]
 pub struct Utilisateur {
     pub id: Uuid, 
     pub email: String, 
     pub mot_de_passe: String, 
     pub prenom: String, 
     pub nom: String, 
-    pub role : String,
+    pub role : Role,
+    pub email_verifie: bool,
     pub date_creation: DateTime<Utc>,
-    pub date_update : DateTime<Utc>
+    pub date_update : DateTime<Utc>,
+    // Suppression douce, annulable jusqu'à la purge
+    pub date_suppression: Option<DateTime<Utc>>,
 }
 
 // src/domaine/modeles/utilisateur.rs (ajouté au même fichier)
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Longueurs alignées sur les VARCHAR de la table utilisateur
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateUser {
+    #[validate(email(message = "Adresse email invalide"), length(max = 255, message = "255 caractères maximum"))]
     pub email: String,
+    #[validate(custom = "politique_mot_de_passe")]
     pub mot_de_passe: String,
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
     pub prenom: String,
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
     pub nom: String,
 }
 
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl CreateUser {
+    // À appeler avant la validation : la forme normalisée est celle qui est validée et stockée
+    pub fn normalisee(mut self) -> Self {
+        self.email = normaliser_email(&self.email);
+        self
+    }
+}
+
+// Remplacement complet des champs modifiables (PUT, et résultat d'un PATCH)
+// Le mot de passe est en écriture seule : absent, il reste inchangé
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateUser {
-    pub email: Option<String>,
+    #[validate(email(message = "Adresse email invalide"), length(max = 255, message = "255 caractères maximum"))]
+    pub email: String,
+    #[validate(custom = "politique_mot_de_passe")]
     pub mot_de_passe: Option<String>,
-    pub prenom: Option<String>,
-    pub nom: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
+    pub prenom: String,
+    #[validate(length(min = 1, max = 100, message = "Entre 1 et 100 caractères"))]
+    pub nom: String,
+}
+
+impl UpdateUser {
+    pub fn normalisee(mut self) -> Self {
+        self.email = normaliser_email(&self.email);
+        self
+    }
+}
+
+// Corps d'un PATCH, selon son Content-Type
+pub enum Correctif {
+    // application/merge-patch+json (RFC 7396)
+    Fusion(Value),
+    // application/json-patch+json (RFC 6902)
+    Operations(Patch),
+}
+
+impl Correctif {
+    // Applique le correctif au document modifiable de l'utilisateur ; le résultat doit être un UpdateUser complet
+    pub fn appliquer(&self, utilisateur: &Utilisateur) -> Result<UpdateUser, MyError> {
+        let mut document = utilisateur.document_modifiable();
+        match self {
+            Correctif::Fusion(correctif) => json_patch::merge(&mut document, correctif),
+            Correctif::Operations(operations) => json_patch::patch(&mut document, operations)
+                .map_err(|e| MyError::ChampsInvalides(vec![ChampInvalide {
+                    champ: "patch".to_string(),
+                    code: "operation".to_string(),
+                    message: e.to_string(),
+                }]))?,
+        }
+        serde_json::from_value(document).map_err(|e| MyError::ChampsInvalides(vec![ChampInvalide {
+            champ: "patch".to_string(),
+            code: "document".to_string(),
+            message: e.to_string(),
+        }]))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TriUtilisateurs {
+    #[default]
+    DateCreation,
+    Nom,
+    Email,
+}
+
+impl TriUtilisateurs {
+    pub fn colonne(&self) -> &'static str {
+        match self {
+            TriUtilisateurs::DateCreation => "date_creation",
+            TriUtilisateurs::Nom => "nom",
+            TriUtilisateurs::Email => "email",
+        }
+    }
+}
+
+// Paramètres de GET /user : page/limite ou curseur, tri et filtres
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RequeteUtilisateurs {
+    pub page: Option<u32>,
+    pub limite: Option<u32>,
+    pub curseur: Option<String>,
+    #[serde(default)]
+    pub tri: TriUtilisateurs,
+    #[serde(default)]
+    pub ordre: Ordre,
+    pub role: Option<Role>,
+    pub cree_apres: Option<DateTime<Utc>>,
+    // Recherche sur prenom, nom et email
+    pub recherche: Option<String>,
+    // Comptes supprimés inclus (Permission::GererSuppressions)
+    #[serde(default)]
+    pub inclure_supprimes: bool,
 }
 
 impl  Utilisateur {
-    pub fn new(create_user: CreateUser) -> Self {
+    pub async fn new(create_user: CreateUser, hacheur: &HacheurMotDePasse) -> Result<Self, MyError> {
         let now = Utc::now();
-        Utilisateur{
+        Ok(Utilisateur{
             id: Uuid::new_v4(), // Génère un nouvel UUID
             email: create_user.email,
-            mot_de_passe: create_user.mot_de_passe, // À hacher dans une application réelle
+            mot_de_passe: hacheur.hacher(&create_user.mot_de_passe).await?,
             prenom: create_user.prenom,
             nom: create_user.nom,
-            role: "User".to_string(),
+            role: Role::Client,
+            email_verifie: false,
             date_creation: now,
             date_update: now,
+            date_suppression: None,
+        })
+    }
+
+    // Champs que PUT et PATCH peuvent modifier (le rôle passe par changer_role)
+    pub fn document_modifiable(&self) -> Value {
+        json!({
+            "email": self.email,
+            "prenom": self.prenom,
+            "nom": self.nom,
+        })
+    }
+
+    pub fn etag(&self) -> String {
+        concurrence::etag(self.date_update)
+    }
+}
+
+
+// Vue d'un compte par un tiers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfilPublic {
+    pub id: Uuid,
+    pub prenom: String,
+    pub nom: String,
+}
+
+// Vue de l'utilisateur sur son propre compte (et du personnel)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfilPersonnel {
+    pub id: Uuid,
+    pub email: String,
+    pub prenom: String,
+    pub nom: String,
+    pub role: Role,
+    pub email_verifie: bool,
+    pub date_creation: DateTime<Utc>,
+}
+
+// Vue administrateur
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfilAdmin {
+    pub id: Uuid,
+    pub email: String,
+    pub prenom: String,
+    pub nom: String,
+    pub role: Role,
+    pub email_verifie: bool,
+    pub date_creation: DateTime<Utc>,
+    pub date_update: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_suppression: Option<DateTime<Utc>>,
+}
+
+impl From<&Utilisateur> for ProfilPublic {
+    fn from(utilisateur: &Utilisateur) -> Self {
+        ProfilPublic {
+            id: utilisateur.id,
+            prenom: utilisateur.prenom.clone(),
+            nom: utilisateur.nom.clone(),
+        }
+    }
+}
+
+impl From<&Utilisateur> for ProfilPersonnel {
+    fn from(utilisateur: &Utilisateur) -> Self {
+        ProfilPersonnel {
+            id: utilisateur.id,
+            email: utilisateur.email.clone(),
+            prenom: utilisateur.prenom.clone(),
+            nom: utilisateur.nom.clone(),
+            role: utilisateur.role,
+            email_verifie: utilisateur.email_verifie,
+            date_creation: utilisateur.date_creation,
+        }
+    }
+}
+
+impl From<&Utilisateur> for ProfilAdmin {
+    fn from(utilisateur: &Utilisateur) -> Self {
+        ProfilAdmin {
+            id: utilisateur.id,
+            email: utilisateur.email.clone(),
+            prenom: utilisateur.prenom.clone(),
+            nom: utilisateur.nom.clone(),
+            role: utilisateur.role,
+            email_verifie: utilisateur.email_verifie,
+            date_creation: utilisateur.date_creation,
+            date_update: utilisateur.date_update,
+            date_suppression: utilisateur.date_suppression,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum VueUtilisateur {
+    Public(ProfilPublic),
+    Personnel(ProfilPersonnel),
+    Admin(ProfilAdmin),
+}
+
+impl VueUtilisateur {
+    // Choisit la vue selon qui lit le compte
+    pub fn pour(lecteur: &Utilisateur, cible: &Utilisateur) -> Self {
+        if lecteur.role.a_permission(Permission::VoirDetailsComptes) {
+            VueUtilisateur::Admin(cible.into())
+        } else if lecteur.id == cible.id || lecteur.role.a_permission(Permission::LireUtilisateurs) {
+            VueUtilisateur::Personnel(cible.into())
+        } else {
+            VueUtilisateur::Public(cible.into())
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utilisateur() -> Utilisateur {
+        let maintenant = Utc::now();
+        Utilisateur {
+            id: Uuid::new_v4(),
+            email: "jean.dupont@exemple.fr".to_string(),
+            mot_de_passe: "hache".to_string(),
+            prenom: "Jean".to_string(),
+            nom: "Dupont".to_string(),
+            role: Role::Client,
+            email_verifie: false,
+            date_creation: maintenant,
+            date_update: maintenant,
+            date_suppression: None,
+        }
+    }
+
+    fn operations(valeur: Value) -> Correctif {
+        Correctif::Operations(serde_json::from_value(valeur).unwrap())
+    }
+
+    fn code_refus(resultat: Result<UpdateUser, MyError>) -> String {
+        match resultat {
+            Err(MyError::ChampsInvalides(champs)) => champs[0].code.clone(),
+            autre => panic!("refus attendu, obtenu {:?}", autre),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fusion_modifie_les_champs_presents() {
+        let modification = Correctif::Fusion(json!({ "prenom": "Jeanne" })).appliquer(&utilisateur()).unwrap();
+        assert_eq!(modification.prenom, "Jeanne");
+        assert_eq!(modification.nom, "Dupont");
+        assert_eq!(modification.mot_de_passe, None);
+    }
+
+    #[test]
+    fn fusion_null_retire_le_champ() {
+        // Champ facultatif : retiré, le mot de passe reste inchangé
+        let modification = Correctif::Fusion(json!({ "mot_de_passe": null })).appliquer(&utilisateur()).unwrap();
+        assert_eq!(modification.mot_de_passe, None);
+        // Champ obligatoire : le document n'est plus un remplacement complet
+        let resultat = Correctif::Fusion(json!({ "nom": null })).appliquer(&utilisateur());
+        assert_eq!(code_refus(resultat), "document");
+    }
+
+    #[test]
+    fn operation_test_en_echec_refusee() {
+        let correctif = operations(json!([
+            { "op": "test", "path": "/nom", "value": "Durand" },
+            { "op": "replace", "path": "/nom", "value": "Martin" },
+        ]));
+        assert_eq!(code_refus(correctif.appliquer(&utilisateur())), "operation");
+        // Le hash n'est pas dans le document : impossible de le sonder
+        let sonde = operations(json!([{ "op": "test", "path": "/mot_de_passe", "value": "hache" }]));
+        assert_eq!(code_refus(sonde.appliquer(&utilisateur())), "operation");
+    }
+
+    #[test]
+    fn operation_test_reussie_appliquee() {
+        let correctif = operations(json!([
+            { "op": "test", "path": "/nom", "value": "Dupont" },
+            { "op": "replace", "path": "/nom", "value": "Martin" },
+        ]));
+        assert_eq!(correctif.appliquer(&utilisateur()).unwrap().nom, "Martin");
+    }
+
+    #[test]
+    fn champs_hors_document_refuses() {
+        for champ in ["id", "role", "date_creation", "email_verifie"] {
+            let fusion = Correctif::Fusion(json!({ champ: "valeur" }));
+            assert_eq!(code_refus(fusion.appliquer(&utilisateur())), "document", "{}", champ);
+            let ajout = operations(json!([{ "op": "add", "path": format!("/{}", champ), "value": "valeur" }]));
+            assert_eq!(code_refus(ajout.appliquer(&utilisateur())), "document", "{}", champ);
+        }
+    }
+
+    #[test]
+    fn mot_de_passe_soumis_a_la_politique() {
+        // Écriture seule : accepté par le correctif, puis validé comme à la création
+        let faible = Correctif::Fusion(json!({ "mot_de_passe": "court" })).appliquer(&utilisateur()).unwrap();
+        let erreurs = faible.validate().unwrap_err();
+        assert!(erreurs.field_errors().contains_key("mot_de_passe"));
+
+        let fort = operations(json!([{ "op": "add", "path": "/mot_de_passe", "value": "solide123" }]))
+            .appliquer(&utilisateur())
+            .unwrap();
+        assert!(fort.validate().is_ok());
+        assert_eq!(fort.mot_de_passe.as_deref(), Some("solide123"));
+    }
+}
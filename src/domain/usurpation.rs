@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::error::MyError;
+
+// Actions refusées à un jeton d'usurpation : elles engagent le titulaire du compte
+// (les paiements s'y ajouteront avec les commandes)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionSensible {
+    MotDePasse,
+    Email,
+    Telephone,
+    DoubleFacteur,
+    SuppressionCompte,
+    ExportDonnees,
+    Sessions,
+}
+
+impl ActionSensible {
+    fn libelle(&self) -> &'static str {
+        match self {
+            ActionSensible::MotDePasse => "changement de mot de passe",
+            ActionSensible::Email => "changement d'email",
+            ActionSensible::Telephone => "changement de numéro de téléphone",
+            ActionSensible::DoubleFacteur => "gestion du double facteur",
+            ActionSensible::SuppressionCompte => "suppression du compte",
+            ActionSensible::ExportDonnees => "export des données personnelles",
+            ActionSensible::Sessions => "révocation des sessions",
+        }
+    }
+
+    pub fn refus(&self) -> MyError {
+        MyError::Forbidden(format!("Interdit pendant une usurpation d'identité: {}", self.libelle()))
+    }
+}
+
+// Réponse de POST /auth/impersonate/{id} : jeton d'accès seul, sans rafraîchissement
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JetonUsurpation {
+    pub jeton_acces: String,
+    pub type_jeton: String,
+    pub expire_dans: i64,
+    pub utilisateur_id: Uuid,
+    pub usurpateur_id: Uuid,
+}
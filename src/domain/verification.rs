@@ -0,0 +1,57 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::error::MyError;
+use crate::domain::user::Utilisateur;
+
+// Actions pouvant être refusées tant que l'email n'est pas vérifié
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionRestreinte {
+    Connexion,
+}
+
+impl ActionRestreinte {
+    fn depuis_nom(nom: &str) -> Option<Self> {
+        match nom.trim() {
+            "connexion" => Some(ActionRestreinte::Connexion),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RestrictionsEmailNonVerifie {
+    actions: Vec<ActionRestreinte>,
+}
+
+impl RestrictionsEmailNonVerifie {
+    pub fn new(actions: Vec<ActionRestreinte>) -> Self {
+        Self { actions }
+    }
+
+    // RESTRICTIONS_EMAIL_NON_VERIFIE=connexion (aucune restriction par défaut)
+    pub fn depuis_env() -> Self {
+        let liste = env::var("RESTRICTIONS_EMAIL_NON_VERIFIE").unwrap_or_default();
+        let actions = liste
+            .split(',')
+            .filter(|nom| !nom.trim().is_empty())
+            .filter_map(|nom| {
+                let action = ActionRestreinte::depuis_nom(nom);
+                if action.is_none() {
+                    tracing::warn!("Action restreinte inconnue ignorée: {}", nom);
+                }
+                action
+            })
+            .collect();
+        Self::new(actions)
+    }
+
+    pub fn verifier(&self, utilisateur: &Utilisateur, action: ActionRestreinte) -> Result<(), MyError> {
+        if !utilisateur.email_verifie && self.actions.contains(&action) {
+            return Err(MyError::Forbidden("Adresse email non vérifiée".to_string()));
+        }
+        Ok(())
+    }
+}
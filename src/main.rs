@@ -1,16 +1,51 @@
-use actix_web::{web, App, HttpServer};
+use actix_web::{middleware, web, App, HttpServer};
 use dotenv::dotenv;
 use std::env;
 use std::sync::Arc;
-use tracing_subscriber;
 
 mod domain;
 mod ports;
 mod adaptateurs;
 
-use adaptateurs::entrer::users::configurer_routes;
+use adaptateurs::entrer::{audit, auth, categories, cles_api, maintenance, produits, sessions, users};
 use adaptateurs::sortie::users::PostgreSql;
+use adaptateurs::sortie::sessions::{lancer_balayeur, PostgreSqlSession};
+use adaptateurs::sortie::jetons::PostgreSqlJeton;
+use adaptateurs::sortie::mail;
+use adaptateurs::sortie::tentatives::PostgreSqlTentatives;
+use adaptateurs::sortie::double_facteur::PostgreSqlDoubleFacteur;
+use adaptateurs::sortie::donnees_personnelles::{lancer_purge, PostgreSqlDonneesPersonnelles};
+use adaptateurs::sortie::audit::PostgreSqlAudit;
+use adaptateurs::sortie::cles_api::PostgreSqlClesApi;
+use adaptateurs::sortie::oidc::ClientOidc;
+use adaptateurs::sortie::identites_externes::PostgreSqlIdentitesExternes;
+use adaptateurs::sortie::telephones::PostgreSqlTelephones;
+use adaptateurs::sortie::sms;
+use adaptateurs::sortie::produits::PostgreSqlProduits;
+use adaptateurs::sortie::categories::PostgreSqlCategories;
 use ports::users::UtilisateurEntree;
+use ports::sessions::SessionPort;
+use ports::jetons::JetonPort;
+use ports::tentatives::TentativesPort;
+use ports::double_facteur::DoubleFacteurPort;
+use ports::donnees_personnelles::DonneesPersonnellesPort;
+use ports::audit::AuditPort;
+use ports::cles_api::ClesApiPort;
+use ports::oidc::{FournisseurOidcPort, IdentitesExternesPort};
+use ports::telephones::TelephonePort;
+use ports::produits::ProduitPort;
+use ports::categories::CategoriePort;
+use domain::mot_de_passe::HacheurMotDePasse;
+use domain::auth::ServiceJwt;
+use domain::session::ConfigSession;
+use domain::error::MyError;
+use domain::jeton::ConfigJetons;
+use domain::verification::RestrictionsEmailNonVerifie;
+use domain::email::ReglesEmail;
+use domain::tentatives::PolitiqueTentatives;
+use domain::totp::ConfigTotp;
+use domain::donnees_personnelles::ConfigRetention;
+use domain::oidc::ConfigOidc;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -24,17 +59,103 @@ async fn main() -> std::io::Result<()> {
 
     // Récupérer l'URL de la base de données
     let database_url = env::var("DATABASE_URL")
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("DATABASE_URL must be set: {}", e)))?;
+        .map_err(|e| std::io::Error::other(format!("DATABASE_URL must be set: {}", e)))?;
 
     // Connexion à la base de données
     let pool = sqlx::postgres::PgPool::connect(&database_url)
         .await
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to connect to database: {}", e)))?;
+        .map_err(|e| std::io::Error::other(format!("Failed to connect to database: {}", e)))?;
+
+    // Règles des fournisseurs appliquées à la clé email (EMAIL_REGLES_FOURNISSEURS)
+    let regles_email = ReglesEmail::depuis_env();
 
     // Initialisation du repository
-    let repo = PostgreSql::new(pool);
-    // Encapsuler le repository dans un Arc<web::Data<dyn UtilisateurEntree>>
-    let repo_data = Arc::new(web::Data::new(Box::new(repo) as Box<dyn UtilisateurEntree>));
+    let repo: Arc<dyn UtilisateurEntree> = Arc::new(PostgreSql::new(pool.clone(), regles_email.clone()));
+
+    // Maintenance : `normaliser-emails [--appliquer]` affiche le rapport puis quitte
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    if arguments.first().map(String::as_str) == Some("normaliser-emails") {
+        let appliquer = arguments.iter().any(|argument| argument == "--appliquer");
+        return maintenance::normaliser_emails(repo.as_ref(), &regles_email, appliquer)
+            .await
+            .map_err(|e| std::io::Error::other(e.to_string()));
+    }
+
+    // web::Data<dyn UtilisateurEntree>, tel qu'extrait par les handlers
+    let repo_data = web::Data::from(repo.clone());
+
+    // Service de hachage des mots de passe (coût configurable via BCRYPT_COST)
+    let hacheur = HacheurMotDePasse::depuis_env()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let hacheur_data = web::Data::new(hacheur);
+
+    // Signature des jetons JWT (secret et durées via JWT_*)
+    let jwt = ServiceJwt::depuis_env()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    let jwt_data = web::Data::new(jwt);
+
+    // Sessions serveur (invités et comptes) et purge périodique des sessions expirées
+    let config_session = ConfigSession::depuis_env();
+    let session_repo: Arc<dyn SessionPort> = Arc::new(PostgreSqlSession::new(pool.clone()));
+    lancer_balayeur(session_repo.clone(), config_session.intervalle_balayage);
+    let session_data = web::Data::from(session_repo);
+    let config_session_data = web::Data::new(config_session);
+
+    // Jetons à usage unique envoyés par mail (MAIL_TRANSPORT=smtp|fichier)
+    let jeton_repo: Arc<dyn JetonPort> = Arc::new(PostgreSqlJeton::new(pool.clone()));
+    let jeton_data = web::Data::from(jeton_repo);
+    let mail_data = web::Data::from(mail::depuis_env().map_err(|e| std::io::Error::other(e.to_string()))?);
+    let config_jetons_data = web::Data::new(ConfigJetons::depuis_env());
+    let restrictions_data = web::Data::new(RestrictionsEmailNonVerifie::depuis_env());
+    let regles_email_data = web::Data::new(regles_email.clone());
+
+    // Protection contre le bruteforce : délais exponentiels et verrouillage (CONNEXION_*)
+    let tentatives_repo: Arc<dyn TentativesPort> = Arc::new(PostgreSqlTentatives::new(pool.clone()));
+    let tentatives_data = web::Data::from(tentatives_repo);
+    let politique_tentatives_data = web::Data::new(PolitiqueTentatives::depuis_env());
+
+    // Second facteur TOTP optionnel (émetteur affiché via TOTP_EMETTEUR)
+    let double_facteur_repo: Arc<dyn DoubleFacteurPort> = Arc::new(PostgreSqlDoubleFacteur::new(pool.clone()));
+    let double_facteur_data = web::Data::from(double_facteur_repo);
+    let config_totp_data = web::Data::new(ConfigTotp::depuis_env());
+
+    // Export et effacement RGPD des données d'un compte
+    let donnees_repo: Arc<dyn DonneesPersonnellesPort> = Arc::new(PostgreSqlDonneesPersonnelles::new(pool.clone(), regles_email.clone()));
+
+    // Journal d'audit en ajout seul des actions sur les comptes
+    let audit_repo: Arc<dyn AuditPort> = Arc::new(PostgreSqlAudit::new(pool.clone()));
+
+    // Clés d'API des comptes de service (Authorization: ApiKey ...)
+    let cles_api_repo: Arc<dyn ClesApiPort> = Arc::new(PostgreSqlClesApi::new(pool.clone()));
+    let cles_api_data = web::Data::from(cles_api_repo);
+
+    // Connexion via des fournisseurs OpenID Connect (OIDC_FOURNISSEURS et OIDC_<NOM>_*)
+    let oidc: Arc<dyn FournisseurOidcPort> = Arc::new(ClientOidc::new(ConfigOidc::depuis_env()));
+    let oidc_data = web::Data::from(oidc);
+    let identites_repo: Arc<dyn IdentitesExternesPort> = Arc::new(PostgreSqlIdentitesExternes::new(pool.clone()));
+    let identites_data = web::Data::from(identites_repo);
+
+    // Connexion sans mot de passe : liens magiques par mail, codes par SMS (SMS_TRANSPORT=journal)
+    let telephones_repo: Arc<dyn TelephonePort> = Arc::new(PostgreSqlTelephones::new(pool.clone()));
+    let telephones_data = web::Data::from(telephones_repo);
+    let sms_data = web::Data::from(sms::depuis_env().map_err(|e| std::io::Error::other(e.to_string()))?);
+
+    // Catalogue : produits et arborescence des catégories
+    let produits_repo: Arc<dyn ProduitPort> = Arc::new(PostgreSqlProduits::new(pool.clone()));
+    let produits_data = web::Data::from(produits_repo);
+    let categories_repo: Arc<dyn CategoriePort> = Arc::new(PostgreSqlCategories::new(pool));
+    let categories_data = web::Data::from(categories_repo);
+
+    // Purge définitive des comptes supprimés au-delà de la rétention (RETENTION_SUPPRESSION_SECS)
+    lancer_purge(
+        repo,
+        donnees_repo.clone(),
+        hacheur_data.clone().into_inner(),
+        audit_repo.clone(),
+        ConfigRetention::depuis_env(),
+    );
+    let donnees_data = web::Data::from(donnees_repo);
+    let audit_data = web::Data::from(audit_repo);
 
     println!("Le serveur est disponible sur http://127.0.0.1:8080");
     tracing::info!("Starting server on 0.0.0.0:8080");
@@ -42,8 +163,44 @@ async fn main() -> std::io::Result<()> {
     // Lancement du serveur HTTP
     HttpServer::new(move || {
         App::new()
+            // Corps JSON illisible : même format d'erreur que le reste de l'API
+            .app_data(web::JsonConfig::default().error_handler(|err, _| {
+                MyError::BadRequest(err.to_string()).into()
+            }))
             .app_data(repo_data.clone()) // Partage du repository avec les handlers
-            .configure(configurer_routes) // Configuration des routes
+            .app_data(hacheur_data.clone())
+            .app_data(jwt_data.clone())
+            .app_data(session_data.clone())
+            .app_data(config_session_data.clone())
+            .app_data(jeton_data.clone())
+            .app_data(mail_data.clone())
+            .app_data(config_jetons_data.clone())
+            .app_data(restrictions_data.clone())
+            .app_data(regles_email_data.clone())
+            .app_data(tentatives_data.clone())
+            .app_data(politique_tentatives_data.clone())
+            .app_data(double_facteur_data.clone())
+            .app_data(config_totp_data.clone())
+            .app_data(donnees_data.clone())
+            .app_data(audit_data.clone())
+            .app_data(cles_api_data.clone())
+            .app_data(oidc_data.clone())
+            .app_data(identites_data.clone())
+            .app_data(telephones_data.clone())
+            .app_data(sms_data.clone())
+            .app_data(produits_data.clone())
+            .app_data(categories_data.clone())
+            // Requêtes sous usurpation d'identité : en-tête X-Usurpation et entrée d'audit
+            .wrap(middleware::from_fn(audit::journaliser_usurpation))
+            // X-Request-Id, repris dans le journal d'audit
+            .wrap(middleware::from_fn(audit::identifiant_requete))
+            .configure(users::configurer_routes) // Configuration des routes
+            .configure(auth::configurer_routes)
+            .configure(sessions::configurer_routes)
+            .configure(audit::configurer_routes)
+            .configure(cles_api::configurer_routes)
+            .configure(produits::configurer_routes)
+            .configure(categories::configurer_routes)
     })
     .bind(("127.0.0.1", 8080))? // Lancer le serveur sur le port 8080
     .run()
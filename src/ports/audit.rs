@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::audit::{EntreeAudit, RequeteAudit};
+use crate::domain::pagination::Page;
+use crate::domain::error::MyError;
+
+// Journal en ajout seul : aucune méthode de modification ou de suppression
+#[async_trait]
+pub trait AuditPort: Send + Sync {
+    async fn enregistrer(&self, entree: &EntreeAudit) -> Result<(), MyError>;
+    async fn lister(&self, requete: &RequeteAudit) -> Result<Page<EntreeAudit>, MyError>;
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::models::Categorie;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait CategoriePort: Send + Sync {
+    async fn creer(&self, categorie: &Categorie) -> Result<Categorie, MyError>;
+    async fn obtenir(&self, id: Uuid) -> Result<Option<Categorie>, MyError>;
+    // Toutes les catégories, triées par nom
+    async fn lister(&self) -> Result<Vec<Categorie>, MyError>;
+    // Nom et description ; le parent ne change que par deplacer
+    async fn mettre_a_jour(&self, categorie: &Categorie) -> Result<Categorie, MyError>;
+    // Refusé si le nouveau parent est la catégorie elle-même ou l'un de ses descendants
+    async fn deplacer(&self, id: Uuid, parent_id: Option<Uuid>) -> Result<Categorie, MyError>;
+    // Refusée tant qu'il reste des sous-catégories ; les produits perdent leur catégorie
+    async fn supprimer(&self, id: Uuid) -> Result<(), MyError>;
+    // Ancêtres de la racine jusqu'à la catégorie elle-même ; vide si elle n'existe pas
+    async fn fil_ariane(&self, id: Uuid) -> Result<Vec<Categorie>, MyError>;
+}
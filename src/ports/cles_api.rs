@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::cle_api::CleApi;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait ClesApiPort: Send + Sync {
+    async fn creer(&self, cle: &CleApi) -> Result<CleApi, MyError>;
+    // Révoquées comprises, des plus récentes aux plus anciennes
+    async fn lister(&self, utilisateur_id: Uuid) -> Result<Vec<CleApi>, MyError>;
+    async fn obtenir(&self, id: Uuid) -> Result<Option<CleApi>, MyError>;
+    // Clé non révoquée et non expirée correspondant à ce hash
+    async fn obtenir_valide(&self, cle_hachee: &str) -> Result<Option<CleApi>, MyError>;
+    async fn marquer_utilisation(&self, id: Uuid) -> Result<(), MyError>;
+    async fn revoquer(&self, id: Uuid) -> Result<CleApi, MyError>;
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::domain::donnees_personnelles::Anonymisation;
+use crate::domain::concurrence::Precondition;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait DonneesPersonnellesPort: Send + Sync {
+    // None si le compte n'existe pas
+    async fn exporter(&self, utilisateur_id: Uuid) -> Result<Option<Value>, MyError>;
+    // Anonymise le compte et supprime ce qui n'a pas à être conservé, en une transaction
+    // Le compte anonymisé conservé est marqué supprimé : les lectures d'utilisateurs l'ignorent
+    async fn effacer(
+        &self,
+        utilisateur_id: Uuid,
+        anonymisation: &Anonymisation,
+        precondition: &Precondition,
+    ) -> Result<(), MyError>;
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::totp::DoubleFacteur;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait DoubleFacteurPort: Send + Sync {
+    async fn obtenir(&self, utilisateur_id: Uuid) -> Result<Option<DoubleFacteur>, MyError>;
+    // Remplace un éventuel secret en attente de confirmation
+    async fn enregistrer(&self, facteur: &DoubleFacteur) -> Result<(), MyError>;
+    async fn activer(&self, utilisateur_id: Uuid, pas: i64, codes_haches: &[String]) -> Result<(), MyError>;
+    // Faux si ce pas de temps a déjà servi (rejeu)
+    async fn marquer_pas(&self, utilisateur_id: Uuid, pas: i64) -> Result<bool, MyError>;
+    async fn consommer_code_recuperation(&self, utilisateur_id: Uuid, code_hache: &str) -> Result<bool, MyError>;
+    async fn desactiver(&self, utilisateur_id: Uuid) -> Result<(), MyError>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::jeton::{JetonUsageUnique, ObjetJeton};
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait JetonPort: Send + Sync {
+    async fn creer(&self, jeton: &JetonUsageUnique) -> Result<(), MyError>;
+    // Marque le jeton comme utilisé s'il est valide et renvoie son propriétaire
+    async fn consommer(&self, objet: ObjetJeton, jeton_hache: &str) -> Result<Option<Uuid>, MyError>;
+    // Enregistre un jeton déjà utilisé ; false s'il l'avait déjà été (rejeu)
+    async fn marquer_utilise(&self, jeton: &JetonUsageUnique) -> Result<bool, MyError>;
+    async fn revoquer_tous(&self, utilisateur_id: Uuid, objet: ObjetJeton) -> Result<(), MyError>;
+}
@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::domain::courriel::Courriel;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait MailPort: Send + Sync {
+    async fn envoyer(&self, courriel: &Courriel) -> Result<(), MyError>;
+}
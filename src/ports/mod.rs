@@ -1 +1,14 @@
 pub mod  users;
+pub mod sessions;
+pub mod jetons;
+pub mod mail;
+pub mod tentatives;
+pub mod double_facteur;
+pub mod donnees_personnelles;
+pub mod audit;
+pub mod cles_api;
+pub mod oidc;
+pub mod sms;
+pub mod telephones;
+pub mod produits;
+pub mod categories;
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::oidc::{DemandeOidc, IdentiteExterne, ProfilOidc};
+use crate::domain::error::MyError;
+
+// Échanges HTTP avec les fournisseurs configurés (découverte, jeton, JWKS)
+#[async_trait]
+pub trait FournisseurOidcPort: Send + Sync {
+    // URL de l'endpoint d'autorisation, avec state, nonce et défi PKCE ; NotFound si le fournisseur est inconnu
+    async fn url_autorisation(&self, demande: &DemandeOidc, etat: &str) -> Result<String, MyError>;
+    // Échange le code contre un id_token et renvoie l'identité qu'il atteste
+    async fn authentifier(&self, demande: &DemandeOidc, code: &str) -> Result<ProfilOidc, MyError>;
+}
+
+#[async_trait]
+pub trait IdentitesExternesPort: Send + Sync {
+    async fn creer_demande(&self, demande: &DemandeOidc) -> Result<(), MyError>;
+    // Supprime la demande et la renvoie si elle n'a pas expiré
+    async fn consommer_demande(&self, etat_hache: &str) -> Result<Option<DemandeOidc>, MyError>;
+    async fn obtenir(&self, fournisseur: &str, sujet: &str) -> Result<Option<IdentiteExterne>, MyError>;
+    async fn lier(&self, identite: &IdentiteExterne) -> Result<IdentiteExterne, MyError>;
+    async fn toucher(&self, id: Uuid) -> Result<(), MyError>;
+}
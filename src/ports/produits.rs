@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::models::Produit;
+use crate::domain::produit::RequeteProduits;
+use crate::domain::pagination::Page;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait ProduitPort: Send + Sync {
+    async fn creer(&self, produit: &Produit) -> Result<Produit, MyError>;
+    async fn obtenir(&self, id: Uuid) -> Result<Option<Produit>, MyError>;
+    async fn lister(&self, requete: &RequeteProduits) -> Result<Page<Produit>, MyError>;
+    async fn mettre_a_jour(&self, produit: &Produit) -> Result<Produit, MyError>;
+    // Refusée tant que le produit figure dans un panier ou une commande
+    async fn supprimer(&self, id: Uuid) -> Result<(), MyError>;
+}
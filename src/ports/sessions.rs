@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::models::Session;
+use crate::domain::session::{JetonRafraichissement, Rotation};
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait SessionPort: Send + Sync {
+    async fn creer(&self, session: &Session) -> Result<Session, MyError>;
+    // Session d'un jeton d'usurpation, révoquée avec sa session parente (celle de l'administrateur)
+    async fn creer_usurpation(&self, session: &Session, parente_id: Option<Uuid>) -> Result<Session, MyError>;
+    // Ne renvoie que les sessions non expirées
+    async fn obtenir_par_token(&self, token: &str) -> Result<Option<Session>, MyError>;
+    async fn prolonger(&self, token: &str, date_expiration: DateTime<Utc>) -> Result<Session, MyError>;
+    async fn rattacher(&self, token: &str, utilisateur_id: Uuid) -> Result<Session, MyError>;
+    // La révocation s'étend aux sessions d'usurpation filles
+    async fn revoquer(&self, token: &str) -> Result<(), MyError>;
+    // Sessions non expirées de l'utilisateur, la plus récemment active en premier
+    async fn lister_actives(&self, utilisateur_id: Uuid) -> Result<Vec<Session>, MyError>;
+    async fn obtenir_par_id(&self, id: Uuid) -> Result<Option<Session>, MyError>;
+    async fn revoquer_par_id(&self, id: Uuid) -> Result<(), MyError>;
+    // « Déconnexion partout » ; renvoie le nombre de sessions révoquées
+    async fn revoquer_tout(&self, utilisateur_id: Uuid) -> Result<u64, MyError>;
+    async fn ajouter_rafraichissement(&self, jeton: &JetonRafraichissement) -> Result<(), MyError>;
+    // Consomme atomiquement le jeton : un même jeton ne peut être Valide qu'une fois
+    async fn consommer_rafraichissement(&self, jeton_hache: &str) -> Result<Rotation, MyError>;
+    // Dernière activité, adresse et nouvelle expiration après un rafraîchissement
+    async fn toucher(&self, id: Uuid, adresse_ip: Option<String>, date_expiration: DateTime<Utc>) -> Result<Session, MyError>;
+    async fn purger_expirees(&self) -> Result<u64, MyError>;
+}
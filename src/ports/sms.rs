@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::domain::sms::Sms;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait SmsPort: Send + Sync {
+    async fn envoyer(&self, sms: &Sms) -> Result<(), MyError>;
+}
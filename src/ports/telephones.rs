@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::sms::{CodeSms, ObjetCodeSms, Telephone};
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait TelephonePort: Send + Sync {
+    async fn obtenir(&self, utilisateur_id: Uuid) -> Result<Option<Telephone>, MyError>;
+    // Numéro vérifié uniquement : seul lui permet la connexion par SMS
+    async fn obtenir_verifie(&self, numero: &str) -> Result<Option<Telephone>, MyError>;
+    // Nouveau numéro, en attente de vérification (remplace le précédent)
+    async fn enregistrer(&self, utilisateur_id: Uuid, numero: &str) -> Result<Telephone, MyError>;
+    async fn marquer_verifie(&self, utilisateur_id: Uuid, numero: &str) -> Result<Telephone, MyError>;
+    // Invalide les codes précédents de même objet
+    async fn creer_code(&self, code: &CodeSms) -> Result<(), MyError>;
+    // Vrai si le code est valide (et le marque utilisé) ; un échec compte un essai
+    async fn consommer_code(
+        &self,
+        utilisateur_id: Uuid,
+        objet: ObjetCodeSms,
+        telephone: &str,
+        code_hache: &str,
+    ) -> Result<bool, MyError>;
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::tentatives::EtatTentatives;
+use crate::domain::error::MyError;
+
+#[async_trait]
+pub trait TentativesPort: Send + Sync {
+    async fn obtenir(&self, cle: &str) -> Result<Option<EtatTentatives>, MyError>;
+    // Incrémente atomiquement le compteur (remis à zéro après fenetre_oubli) et renvoie sa valeur
+    async fn incrementer(&self, cle: &str, fenetre_oubli: Duration) -> Result<i32, MyError>;
+    async fn bloquer(&self, cle: &str, jusqu_a: DateTime<Utc>) -> Result<(), MyError>;
+    async fn effacer(&self, cle: &str) -> Result<(), MyError>;
+}
@@ -1,16 +1,33 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::domain::user::Utilisateur;
+use crate::domain::user::{RequeteUtilisateurs, Utilisateur};
+use crate::domain::pagination::Page;
 use crate::domain::error::MyError;
+use crate::domain::role::Role;
+use crate::domain::concurrence::Precondition;
 
+// Les lectures ignorent les comptes supprimés (date_suppression), sauf mention contraire
 #[async_trait]
 pub trait UtilisateurEntree: Send + Sync  {
     async fn creer(&self, utilisateur: &Utilisateur) -> Result<Utilisateur, MyError>;
     async fn obtenir_par_id(&self, id: Uuid) -> Result<Option<Utilisateur>, MyError>;
     async fn obtenir_par_nom(&self, nom: &str) -> Result<Option<Utilisateur>, MyError>;
+    // Recherche par email_cle : casse et variantes des fournisseurs configurés ignorées
     async fn obtenir_par_email(&self, email: &str) -> Result<Option<Utilisateur>, MyError>;
-    async fn obtenir_tous(&self) -> Result<Vec<Utilisateur>, MyError>;
-    async fn mettre_a_jour(&self, utilisateur: &Utilisateur) -> Result<Utilisateur, MyError>;
-    async fn supprimer(&self, id: Uuid) -> Result<(), MyError>;
+    async fn lister(&self, requete: &RequeteUtilisateurs) -> Result<Page<Utilisateur>, MyError>;
+    // Tous les comptes, supprimés compris, du plus ancien au plus récent (maintenance)
+    async fn lister_tous(&self) -> Result<Vec<Utilisateur>, MyError>;
+    // Écritures conditionnelles : PreconditionEchouee si date_update ne correspond plus
+    async fn mettre_a_jour(&self, utilisateur: &Utilisateur, precondition: &Precondition) -> Result<Utilisateur, MyError>;
+    // Maintenance : réécrit l'email et sa clé, compte supprimé compris ; false si rien n'a changé
+    async fn reecrire_email(&self, id: Uuid, email: &str) -> Result<bool, MyError>;
+    async fn changer_role(&self, id: Uuid, role: Role, precondition: &Precondition) -> Result<Utilisateur, MyError>;
+    // Suppression douce : le compte reste restaurable jusqu'à la purge
+    async fn supprimer(&self, id: Uuid, precondition: &Precondition) -> Result<(), MyError>;
+    // Un compte effacé (anonymisé) ne se restaure pas
+    async fn restaurer(&self, id: Uuid) -> Result<Utilisateur, MyError>;
+    // Comptes supprimés avant cette date, à purger
+    async fn supprimes_avant(&self, limite: DateTime<Utc>) -> Result<Vec<Uuid>, MyError>;
 }
\ No newline at end of file